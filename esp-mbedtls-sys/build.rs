@@ -1,9 +1,12 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
 use std::{env, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use enumset::EnumSet;
 
-use crate::builder::Hook;
+use crate::builder::{Hook, RecordBuffers};
 
 #[path = "gen/builder.rs"]
 mod builder;
@@ -27,6 +30,19 @@ fn main() -> Result<()> {
         .join(format!("{target}.rs"));
     let pregen_libs_dir = crate_root_path.join("libs").join(&target);
 
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+
+    // Generate the table of the symbolic error names from the MbedTLS headers
+    let errors = generate_errors(
+        errors_headers_dir(&crate_root_path, &target).as_deref(),
+        &out,
+    )?;
+
+    println!(
+        "cargo::rustc-env=ESP_MBEDTLS_SYS_ERRORS_FILE={}",
+        errors.display()
+    );
+
     // Figure out what MbedTLS hook options (ALT modules) to enable
     let mut removed_hooks = EnumSet::empty();
 
@@ -41,18 +57,53 @@ fn main() -> Result<()> {
         }
     }
 
-    let dirs = if pregen_bindings && pregen_bindings_rs_file.exists() && removed_hooks.is_empty() {
+    // Figure out the sizing of the TLS record buffers
+    let record_buffers = RecordBuffers {
+        in_content_len: content_len("ESP_MBEDTLS_SSL_IN_CONTENT_LEN")?,
+        out_content_len: content_len("ESP_MBEDTLS_SSL_OUT_CONTENT_LEN")?,
+        variable_length: env::var("CARGO_FEATURE_VARIABLE_BUFFER_LENGTH").is_ok(),
+    };
+
+    let config = builder::MbedtlsBuilder::config(&crate_root_path)?;
+
+    // The options of the MbedTLS library being linked, which decide what functionality `esp-mbedtls` enables
+    let options;
+
+    let dirs = if pregen_bindings
+        && pregen_bindings_rs_file.exists()
+        && removed_hooks.is_empty()
+        && record_buffers.is_default()
+    {
+        // The pre-generated libraries and bindings are used even if they were generated with an older
+        // MbedTLS configuration, in which case the options they lack are not available
+        let pregen_config =
+            builder::MbedtlsBuilder::built_config(&pregen_libs_dir).unwrap_or_default();
+
+        if pregen_config != config {
+            println!("cargo::warning=The pre-generated libraries and bindings for {target} are out of date with `gen/include/config.h`, so the MbedTLS options they lack are not available; re-generate them with `cargo xtask gen {target}`");
+        }
+
+        options = config_options(&pregen_config);
+
         // Use the pre-generated bindings
         Some((pregen_bindings_rs_file, pregen_libs_dir))
     } else if target.ends_with("-espidf") {
         // Nothing to do for ESP-IDF, `esp-idf-sys` will do everything for us
+        if !record_buffers.is_default() {
+            println!("cargo::warning=The record buffers configuration is ignored for ESP-IDF, use the `CONFIG_MBEDTLS_SSL_*` options of `sdkconfig` instead");
+        }
+
+        options = espidf_options();
+
         None
     } else {
         if pregen_bindings_rs_file.exists() {
             if !pregen_bindings {
                 println!("cargo::warning=Forcing on-the-fly build for target {target}");
-            } else {
+            } else if !removed_hooks.is_empty() {
                 println!("cargo::warning=Forcing on-the-fly build for {target} because some or all hooks are disabled: {removed_hooks:?}");
+            } else {
+                println!("cargo::warning=Forcing on-the-fly build for {target} because the record buffers are configured: {record_buffers:?}");
             }
         }
 
@@ -60,10 +111,9 @@ fn main() -> Result<()> {
         let sysroot = (!use_gcc).then(|| crate_root_path.join("gen").join("sysroot"));
 
         // Need to do on-the-fly build and bindings' generation
-        let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
-
         let builder = builder::MbedtlsBuilder::new(
             removed_hooks.complement(),
+            record_buffers,
             !use_gcc,
             crate_root_path.clone(),
             Some(target),
@@ -77,9 +127,14 @@ fn main() -> Result<()> {
         let libs_dir = builder.compile(&out, None)?;
         let bindings = builder.generate_bindings(&out, None)?;
 
+        options = config_options(&config);
+
         Some((bindings, libs_dir))
     };
 
+    // Exported to the build script of `esp-mbedtls` as `DEP_MBEDTLS_OPTIONS`
+    println!("cargo::metadata=OPTIONS={}", options.join(","));
+
     if let Some((bindings, libs_dir)) = dirs {
         println!(
             "cargo::rustc-env=ESP_MBEDTLS_SYS_BINDINGS_FILE={}",
@@ -107,3 +162,207 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// Read the maximum plaintext length of the TLS records from the `var` environment variable
+///
+/// Returns `None` if the variable is not set, in which case the MbedTLS default applies.
+fn content_len(var: &str) -> Result<Option<usize>> {
+    println!("cargo:rerun-if-env-changed={var}");
+
+    let Ok(value) = env::var(var) else {
+        return Ok(None);
+    };
+
+    let len = value
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|len| (512..=16384).contains(len))
+        .ok_or_else(|| anyhow!("`{var}` must be a length between 512 and 16384, got `{value}`"))?;
+
+    Ok(Some(len))
+}
+
+/// Get the MbedTLS options (`MBEDTLS_*` macros) defined by an MbedTLS configuration file
+fn config_options(config: &str) -> Vec<String> {
+    config
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("#define MBEDTLS_"))
+        .filter_map(|define| define.split(|c: char| c.is_whitespace() || c == '(').next())
+        .map(|name| format!("MBEDTLS_{name}"))
+        .collect()
+}
+
+/// Get the MbedTLS options of the MbedTLS library distributed with ESP-IDF
+///
+/// These follow the `CONFIG_MBEDTLS_*` options of the ESP-IDF `sdkconfig`, which are read from the
+/// `sdkconfig.h` header located using the C include arguments exported by `esp-idf-sys`.
+fn espidf_options() -> Vec<String> {
+    let sdkconfig = env::var("DEP_ESP_IDF_EMBUILD_CINCL_ARGS")
+        .ok()
+        .and_then(|args| {
+            include_dirs(&args)
+                .into_iter()
+                .map(|dir| dir.join("sdkconfig.h"))
+                .find(|header| header.exists())
+        })
+        .and_then(|header| std::fs::read_to_string(header).ok());
+
+    let Some(sdkconfig) = sdkconfig else {
+        println!("cargo::warning=The ESP-IDF `sdkconfig.h` header was not found, the functionality depending on optional MbedTLS options is not available");
+        return Vec::new();
+    };
+
+    let mut options = sdkconfig
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("#define CONFIG_MBEDTLS_"))
+        .filter_map(|define| define.split_whitespace().next())
+        .map(|name| format!("MBEDTLS_{name}"))
+        .collect::<Vec<_>>();
+
+    // In ESP-IDF, the DTLS HelloVerifyRequest support comes with DTLS, without an option of its own
+    if options
+        .iter()
+        .any(|option| option == "MBEDTLS_SSL_PROTO_DTLS")
+    {
+        options.push("MBEDTLS_SSL_DTLS_HELLO_VERIFY".to_string());
+    }
+
+    options
+}
+
+/// Find the directory of the MbedTLS headers defining the error codes
+///
+/// For ESP-IDF, these are the headers of the MbedTLS library distributed with ESP-IDF, which are
+/// located using the C include arguments exported by `esp-idf-sys`. For all other targets, these are
+/// the headers of the `mbedtls` submodule.
+///
+/// Returns `None` if the headers are not available.
+fn errors_headers_dir(crate_root_path: &Path, target: &str) -> Option<PathBuf> {
+    if target.ends_with("-espidf") {
+        let args = env::var("DEP_ESP_IDF_EMBUILD_CINCL_ARGS").ok()?;
+
+        include_dirs(&args)
+            .into_iter()
+            .map(|dir| dir.join("mbedtls"))
+            .find(|dir| dir.join("error.h").exists())
+    } else {
+        Some(
+            crate_root_path
+                .join("mbedtls")
+                .join("include")
+                .join("mbedtls"),
+        )
+        .filter(|dir| dir.join("error.h").exists())
+    }
+}
+
+/// Extract the include directories (`-I<dir>` and `-isystem<dir>`) from C compiler arguments,
+/// which might be double-quoted
+fn include_dirs(args: &str) -> Vec<PathBuf> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in args.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    let mut dirs = Vec::new();
+    let mut tokens = tokens.into_iter();
+
+    while let Some(token) = tokens.next() {
+        let dir = match token.as_str() {
+            "-I" | "-isystem" => tokens.next(),
+            _ => token
+                .strip_prefix("-isystem")
+                .or_else(|| token.strip_prefix("-I"))
+                .map(str::to_string),
+        };
+
+        dirs.extend(dir.map(PathBuf::from));
+    }
+
+    dirs
+}
+
+/// Generate a Rust file with the table of all `MBEDTLS_ERR_*` error codes
+/// defined in the MbedTLS headers located in `headers_dir`
+///
+/// Each table entry is a tuple of the (positive) error code and the error name without
+/// the `MBEDTLS_ERR_` prefix.
+///
+/// If the headers are not available, a warning is emitted and an empty table is generated,
+/// so that the errors are reported by code only.
+fn generate_errors(headers_dir: Option<&Path>, out_path: &Path) -> Result<PathBuf> {
+    let mut errors = BTreeMap::new();
+
+    if let Some(headers_dir) = headers_dir {
+        let mut headers = std::fs::read_dir(headers_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        headers.sort();
+
+        for header in headers {
+            if header.extension().and_then(|ext| ext.to_str()) != Some("h") {
+                continue;
+            }
+
+            for line in std::fs::read_to_string(&header)?.lines() {
+                let Some(define) = line.trim_start().strip_prefix("#define MBEDTLS_ERR_") else {
+                    continue;
+                };
+
+                let mut parts = define.split_whitespace();
+                let (Some(name), Some(value)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+
+                let value = value.trim_start_matches('(').trim_end_matches(')');
+                let Some(Ok(code)) = value
+                    .strip_prefix("-0x")
+                    .or_else(|| value.strip_prefix("-0X"))
+                    .map(|hex| u16::from_str_radix(hex, 16))
+                else {
+                    continue;
+                };
+
+                // Keep the first definition in case of aliases
+                errors.entry(code).or_insert_with(|| name.to_string());
+            }
+        }
+    } else {
+        println!("cargo::warning=The MbedTLS headers were not found, the MbedTLS errors will be reported without their symbolic names");
+    }
+
+    let mut table = String::new();
+
+    writeln!(
+        table,
+        "/// All MbedTLS error codes, sorted by code: (code, name)"
+    )?;
+    writeln!(table, "pub(crate) const ERRORS: &[(u16, &str)] = &[")?;
+
+    for (code, name) in errors {
+        writeln!(table, "    (0x{code:04x}, {name:?}),")?;
+    }
+
+    writeln!(table, "];")?;
+
+    let errors_file = out_path.join("errors.rs");
+    std::fs::write(&errors_file, table)?;
+
+    Ok(errors_file)
+}
@@ -19,9 +19,53 @@ pub enum Hook {
     ExpMod,
 }
 
+/// The sizing of the TLS record buffers of MbedTLS
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecordBuffers {
+    /// The maximum plaintext length of incoming records (`MBEDTLS_SSL_IN_CONTENT_LEN`),
+    /// or `None` for the MbedTLS default (16384)
+    pub in_content_len: Option<usize>,
+    /// The maximum plaintext length of outgoing records (`MBEDTLS_SSL_OUT_CONTENT_LEN`),
+    /// or `None` for the MbedTLS default (16384)
+    pub out_content_len: Option<usize>,
+    /// Whether to shrink the record buffers to the negotiated maximum fragment length
+    /// once the handshake is complete (`MBEDTLS_SSL_VARIABLE_BUFFER_LENGTH`)
+    pub variable_length: bool,
+}
+
+impl RecordBuffers {
+    /// Return `true` if the MbedTLS defaults are used, i.e. the configuration in `gen/include/config.h` applies as-is
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The preprocessor definitions overriding the MbedTLS defaults
+    fn defs(&self) -> Vec<String> {
+        let mut defs = Vec::new();
+
+        if let Some(len) = self.in_content_len {
+            defs.push(format!("MBEDTLS_SSL_IN_CONTENT_LEN={len}"));
+        }
+
+        if let Some(len) = self.out_content_len {
+            defs.push(format!("MBEDTLS_SSL_OUT_CONTENT_LEN={len}"));
+        }
+
+        if self.variable_length {
+            defs.push("MBEDTLS_SSL_VARIABLE_BUFFER_LENGTH".to_string());
+        }
+
+        defs
+    }
+}
+
+/// The name of the MbedTLS configuration file, in `gen/include`
+const CONFIG_FILE_NAME: &str = "config.h";
+
 /// The MbedTLS builder
 pub struct MbedtlsBuilder {
     hooks: EnumSet<Hook>,
+    record_buffers: RecordBuffers,
     crate_root_path: PathBuf,
     cmake_configurer: CMakeConfigurer,
     clang_path: Option<PathBuf>,
@@ -34,6 +78,7 @@ impl MbedtlsBuilder {
     ///
     /// Arguments:
     /// - `hooks` - Set of algorithm hooks to enable
+    /// - `record_buffers` - The sizing of the TLS record buffers
     /// - `force_clang`: If true, force the use of Clang as the C/C++ compiler
     /// - `crate_root_path`: Path to the root of the crate
     /// - `cmake_rust_target`: Optional target for CMake when building MbedTLS, with Rust target-triple syntax. If not specified, the "TARGET" env variable will be used
@@ -48,6 +93,7 @@ impl MbedtlsBuilder {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         hooks: EnumSet<Hook>,
+        record_buffers: RecordBuffers,
         force_clang: bool,
         crate_root_path: PathBuf,
         cmake_rust_target: Option<String>,
@@ -59,6 +105,7 @@ impl MbedtlsBuilder {
     ) -> Self {
         Self {
             hooks,
+            record_buffers,
             cmake_configurer: CMakeConfigurer::new(
                 force_clang,
                 clang_sysroot_path.clone(),
@@ -168,6 +215,11 @@ impl MbedtlsBuilder {
             }
         }
 
+        // `MBEDTLS_SSL_VARIABLE_BUFFER_LENGTH` changes the layout of `mbedtls_ssl_context`
+        for def in self.record_buffers.defs() {
+            builder = builder.clang_arg(format!("-D{def}"));
+        }
+
         let bindings = builder
             .generate()
             .map_err(|_| anyhow!("Failed to generate bindings"))?;
@@ -198,6 +250,8 @@ impl MbedtlsBuilder {
     ///
     /// Arguments:
     /// - `out_path`: Path to write the compiled libraries to
+    /// - `copy_path`: Optional path to write the compiled libraries to instead, along with a copy of the
+    ///   MbedTLS configuration they are built with (see `built_config`)
     pub fn compile(&self, out_path: &Path, copy_path: Option<&Path>) -> Result<PathBuf> {
         let target_dir = out_path.join("mbedtls").join("build");
         std::fs::create_dir_all(&target_dir)?;
@@ -208,7 +262,11 @@ impl MbedtlsBuilder {
         std::fs::create_dir_all(lib_dir)?;
 
         // Compile MbedTLS and generate libraries to link against
-        log::info!("Compiling MbedTLS with accel {:?}", self.hooks);
+        log::info!(
+            "Compiling MbedTLS with accel {:?} and record buffers {:?}",
+            self.hooks,
+            self.record_buffers
+        );
 
         let mut config = self.cmake_configurer.configure(Some(lib_dir));
 
@@ -222,10 +280,7 @@ impl MbedtlsBuilder {
             .define("MBEDTLS_FATAL_WARNINGS", "OFF")
             .define(
                 "MBEDTLS_CONFIG_FILE",
-                self.crate_root_path
-                    .join("gen")
-                    .join("include")
-                    .join("config.h"),
+                Self::config_file(&self.crate_root_path),
             )
             .cflag(format!(
                 "-I{}",
@@ -248,11 +303,45 @@ impl MbedtlsBuilder {
             }
         }
 
+        for def in self.record_buffers.defs() {
+            config.cflag(format!("-D{def}")).cxxflag(format!("-D{def}"));
+        }
+
         config.build();
 
+        if copy_path.is_some() {
+            std::fs::copy(
+                Self::config_file(&self.crate_root_path),
+                lib_dir.join(CONFIG_FILE_NAME),
+            )?;
+        }
+
         Ok(lib_dir.to_path_buf())
     }
 
+    /// Get the MbedTLS configuration (`gen/include/config.h`) the pre-built libraries in `libs_dir` were built with
+    ///
+    /// This is the copy of the configuration which is written alongside the libraries by `compile`.
+    /// Returns `None` if there is no such copy.
+    #[allow(unused)]
+    pub fn built_config(libs_dir: &Path) -> Option<String> {
+        std::fs::read_to_string(libs_dir.join(CONFIG_FILE_NAME)).ok()
+    }
+
+    /// Get the current MbedTLS configuration (`gen/include/config.h`)
+    #[allow(unused)]
+    pub fn config(crate_root_path: &Path) -> Result<String> {
+        Ok(std::fs::read_to_string(Self::config_file(crate_root_path))?)
+    }
+
+    /// The MbedTLS configuration file
+    fn config_file(crate_root_path: &Path) -> PathBuf {
+        crate_root_path
+            .join("gen")
+            .join("include")
+            .join(CONFIG_FILE_NAME)
+    }
+
     /// Re-run the build script if the file or directory has changed.
     #[allow(unused)]
     pub fn track(file_or_dir: &Path) {
@@ -21,8 +21,136 @@ impl MbedtlsError {
     pub fn code_normalized(&self) -> Option<u16> {
         (self.0 <= 0 && self.0 >= -65535).then_some(-self.0 as u16)
     }
+
+    /// Get the high-level part of the error code, if any.
+    ///
+    /// MbedTLS error codes returned by the high-level modules (SSL, X509, PK, ...) might be
+    /// combined with the error code of the low-level module (MPI, ASN1, ...) which caused them,
+    /// i.e. `X509_INVALID_FORMAT + ASN1_OUT_OF_DATA`.
+    pub fn high_level(&self) -> Option<MbedtlsError> {
+        let high = self.code_normalized()? & HIGH_LEVEL_MASK;
+
+        (high != 0).then_some(Self(-(high as i32)))
+    }
+
+    /// Get the low-level part of the error code, if any.
+    ///
+    /// See `high_level` for details.
+    pub fn low_level(&self) -> Option<MbedtlsError> {
+        let low = self.code_normalized()? & !HIGH_LEVEL_MASK;
+
+        (low != 0).then_some(Self(-(low as i32)))
+    }
+
+    /// Get the symbolic name of the error code without the `MBEDTLS_ERR_` prefix,
+    /// i.e. `SSL_FATAL_ALERT_MESSAGE`.
+    ///
+    /// Returns `None` if the error code is unknown, or if it is a combination of
+    /// a high-level and a low-level error code (use `high_level` and `low_level` to decompose it).
+    pub fn name(&self) -> Option<&'static str> {
+        let code = self.code_normalized()?;
+
+        ERRORS
+            .binary_search_by_key(&code, |(code, _)| *code)
+            .ok()
+            .map(|index| ERRORS[index].1)
+    }
+
+    /// Get the category (i.e. the MbedTLS module) of the error, in lowercase,
+    /// i.e. `ssl`, `x509`, `pk`, `mpi`, `cipher`, ...
+    ///
+    /// The category is derived from the ranges of error codes assigned to each module, so it is
+    /// available even if the error code itself is unknown.
+    ///
+    /// For combined error codes, this is the category of the high-level part.
+    pub fn category(&self) -> Option<&'static str> {
+        let code = self
+            .high_level()
+            .or_else(|| self.low_level())?
+            .code_normalized()?;
+
+        CATEGORIES
+            .iter()
+            .find(|(first, last, step, _)| {
+                (*first..=*last).contains(&code) && (code - first) % step == 0
+            })
+            .map(|(_, _, _, category)| *category)
+    }
 }
 
+/// The bits of a normalized error code used by the high-level modules.
+const HIGH_LEVEL_MASK: u16 = 0xff80;
+
+/// The ranges of error codes assigned to each MbedTLS module, as documented in `mbedtls/error.h`:
+/// (first code, last code, step, category)
+///
+/// The low-level modules share their ranges, using either the odd or the even codes within,
+/// hence the step. Where the documented ranges overlap, the first match applies.
+const CATEGORIES: &[(u16, u16, u16, &str)] = &[
+    // Low-level modules
+    (0x0001, 0x0001, 1, "error"),
+    (0x006e, 0x006e, 1, "error"),
+    (0x0002, 0x0010, 2, "mpi"),
+    (0x0003, 0x0009, 2, "hmac_drbg"),
+    (0x000b, 0x000b, 1, "oid"),
+    (0x002e, 0x002e, 1, "oid"),
+    (0x0011, 0x0019, 2, "lms"),
+    (0x000d, 0x0011, 2, "ccm"),
+    (0x0012, 0x0016, 2, "gcm"),
+    (0x0013, 0x0013, 1, "gcm"),
+    (0x001a, 0x001e, 2, "threading"),
+    (0x0020, 0x0022, 2, "aes"),
+    (0x0021, 0x0025, 2, "aes"),
+    (0x0024, 0x0026, 2, "camellia"),
+    (0x0027, 0x0027, 1, "camellia"),
+    (0x002a, 0x002c, 2, "base64"),
+    (0x002f, 0x002f, 1, "md5"),
+    (0x0030, 0x0030, 1, "padlock"),
+    (0x0031, 0x0031, 1, "ripemd160"),
+    (0x0032, 0x0033, 1, "des"),
+    (0x0034, 0x003a, 2, "ctr_drbg"),
+    (0x0035, 0x0035, 1, "sha1"),
+    (0x0037, 0x0037, 1, "sha256"),
+    (0x0039, 0x0039, 1, "sha512"),
+    (0x003c, 0x0040, 2, "entropy"),
+    (0x003d, 0x003f, 2, "entropy"),
+    (0x0042, 0x0052, 2, "net"),
+    (0x0043, 0x0049, 2, "net"),
+    (0x0051, 0x0055, 2, "chacha20"),
+    (0x0054, 0x0056, 2, "chachapoly"),
+    (0x0057, 0x005b, 2, "poly1305"),
+    (0x0058, 0x005e, 2, "aria"),
+    (0x0060, 0x006c, 2, "asn1"),
+    (0x0070, 0x0072, 2, "platform"),
+    (0x0073, 0x0073, 1, "sha1"),
+    (0x0074, 0x0074, 1, "sha256"),
+    (0x0075, 0x0075, 1, "sha512"),
+    (0x0076, 0x0076, 1, "sha3"),
+    (0x007a, 0x007a, 1, "cmac"),
+    (0x007c, 0x007c, 1, "pbkdf2"),
+    // High-level modules
+    (0x1080, 0x1480, 0x80, "pem"),
+    (0x1e00, 0x1f80, 0x80, "pkcs12"),
+    (0x2080, 0x2980, 0x80, "x509"),
+    (0x3000, 0x3000, 0x80, "x509"),
+    (0x2e00, 0x2f80, 0x80, "pkcs5"),
+    (0x3080, 0x3580, 0x80, "dhm"),
+    (0x3880, 0x3f80, 0x80, "pk"),
+    (0x4080, 0x4580, 0x80, "rsa"),
+    (0x4b00, 0x4f80, 0x80, "ecp"),
+    (0x5080, 0x5280, 0x80, "md"),
+    (0x5300, 0x5880, 0x80, "pkcs7"),
+    (0x5d80, 0x5f00, 0x80, "ssl"),
+    (0x5f80, 0x5f80, 0x80, "hkdf"),
+    (0x6000, 0x6000, 0x80, "ssl"),
+    (0x6080, 0x6380, 0x80, "cipher"),
+    (0x6400, 0x6f80, 0x80, "ssl"),
+    (0x7000, 0x7f80, 0x80, "ssl"),
+];
+
+// The table of all MbedTLS error codes, generated from the MbedTLS headers by the build script.
+include!(env!("ESP_MBEDTLS_SYS_ERRORS_FILE"));
+
 impl Debug for MbedtlsError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self)
@@ -32,7 +160,18 @@ impl Debug for MbedtlsError {
 impl Display for MbedtlsError {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if let Some(normalized) = self.code_normalized() {
-            write!(f, "MbedtlsError({} / 0x{:04x})", self.0, normalized)
+            write!(f, "MbedtlsError({} / 0x{:04x}", self.0, normalized)?;
+
+            let mut separator = ": ";
+
+            for part in [self.high_level(), self.low_level()].into_iter().flatten() {
+                if let Some(name) = part.name() {
+                    write!(f, "{separator}{name}")?;
+                    separator = " + ";
+                }
+            }
+
+            write!(f, ")")
         } else {
             write!(f, "MbedtlsError({})", self.0)
         }
@@ -43,7 +182,18 @@ impl Display for MbedtlsError {
 impl defmt::Format for MbedtlsError {
     fn format(&self, f: defmt::Formatter) {
         if let Some(normalized) = self.code_normalized() {
-            defmt::write!(f, "MbedtlsError({} / 0x{:04x})", self.0, normalized)
+            defmt::write!(f, "MbedtlsError({} / 0x{:04x}", self.0, normalized);
+
+            let mut separator = ": ";
+
+            for part in [self.high_level(), self.low_level()].into_iter().flatten() {
+                if let Some(name) = part.name() {
+                    defmt::write!(f, "{=str}{=str}", separator, name);
+                    separator = " + ";
+                }
+            }
+
+            defmt::write!(f, ")")
         } else {
             defmt::write!(f, "MbedtlsError({})", self.0)
         }
@@ -63,3 +213,77 @@ macro_rules! merr {
         }
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MbedtlsError, ERRORS};
+    use crate::{
+        MBEDTLS_ERR_ASN1_OUT_OF_DATA, MBEDTLS_ERR_MPI_ALLOC_FAILED, MBEDTLS_ERR_SSL_WANT_READ,
+        MBEDTLS_ERR_X509_INVALID_FORMAT,
+    };
+
+    /// `X509_INVALID_FORMAT + ASN1_OUT_OF_DATA`
+    const COMBINED: MbedtlsError =
+        MbedtlsError::new(MBEDTLS_ERR_X509_INVALID_FORMAT + MBEDTLS_ERR_ASN1_OUT_OF_DATA);
+
+    #[test]
+    fn split() {
+        assert_eq!(COMBINED.code_normalized(), Some(0x21e0));
+        assert_eq!(
+            COMBINED.high_level(),
+            Some(MbedtlsError::new(MBEDTLS_ERR_X509_INVALID_FORMAT))
+        );
+        assert_eq!(
+            COMBINED.low_level(),
+            Some(MbedtlsError::new(MBEDTLS_ERR_ASN1_OUT_OF_DATA))
+        );
+
+        let high = MbedtlsError::new(MBEDTLS_ERR_SSL_WANT_READ);
+        assert_eq!(high.high_level(), Some(high));
+        assert_eq!(high.low_level(), None);
+
+        let low = MbedtlsError::new(MBEDTLS_ERR_MPI_ALLOC_FAILED);
+        assert_eq!(low.high_level(), None);
+        assert_eq!(low.low_level(), Some(low));
+
+        for code in [0, 1, -65536] {
+            let error = MbedtlsError::new(code);
+            assert_eq!(error.high_level(), None);
+            assert_eq!(error.low_level(), None);
+        }
+    }
+
+    #[test]
+    fn combined() {
+        assert_eq!(COMBINED.name(), None);
+        assert_eq!(COMBINED.category(), Some("x509"));
+
+        let (high, low) = (
+            COMBINED.high_level().unwrap(),
+            COMBINED.low_level().unwrap(),
+        );
+        assert_eq!(high.name(), Some("X509_INVALID_FORMAT"));
+        assert_eq!(high.category(), Some("x509"));
+        assert_eq!(low.name(), Some("ASN1_OUT_OF_DATA"));
+        assert_eq!(low.category(), Some("asn1"));
+    }
+
+    /// Check that the category of each known error matches the module prefix of its name
+    #[test]
+    fn categories() {
+        assert!(!ERRORS.is_empty());
+
+        for (code, name) in ERRORS {
+            let category = MbedtlsError::new(-(*code as i32)).category();
+            assert!(category.is_some(), "No category for {name} ({code:#06x})");
+
+            let category = category.unwrap();
+            assert!(
+                name.len() > category.len()
+                    && name[..category.len()].eq_ignore_ascii_case(category)
+                    && name.as_bytes()[category.len()] == b'_',
+                "Category {category} does not match {name} ({code:#06x})"
+            );
+        }
+    }
+}
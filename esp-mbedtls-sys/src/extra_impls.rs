@@ -49,6 +49,37 @@ impl core::fmt::Debug for crate::bindings::mbedtls_x509_crt {
     }
 }
 
+impl core::fmt::Debug for crate::bindings::mbedtls_x509_crl {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut crl: *const crate::bindings::mbedtls_x509_crl = self;
+        let mut index = 0;
+        let mut buf = [0u8; 1024];
+        while !crl.is_null() {
+            index += 1;
+            buf.fill(0);
+            let buf_ptr = buf.as_mut_ptr() as *mut c_char;
+            let ret: c_int = unsafe {
+                crate::bindings::mbedtls_x509_crl_info(buf_ptr, buf.len() - 1, c"".as_ptr(), crl)
+            };
+            if ret < 0 {
+                writeln!(
+                    f,
+                    "CRL #{}: mbedtls_x509_crl_info failed with code {}",
+                    index, ret
+                )?;
+            } else {
+                let cstr = unsafe { CStr::from_ptr(buf_ptr) };
+                match cstr.to_str() {
+                    Ok(s) => write!(f, "\nCRL #{}:\n{}", index, s)?,
+                    Err(_) => writeln!(f, "\nCRL #{}: mbedtls_x509_crl_info returned invalid UTF-8 in output", index)?,
+                }
+            }
+            crl = unsafe { (*crl).next };
+        }
+        Ok(())
+    }
+}
+
 impl core::fmt::Debug for crate::bindings::mbedtls_pk_context {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // TODO: Implement helpful debug
@@ -0,0 +1,32 @@
+use std::env;
+
+/// The optional MbedTLS options some of the functionality of the crate depends on
+///
+/// Each of these which the MbedTLS library linked by `esp-mbedtls-sys` is built with
+/// is turned into a `cfg` of the same name, in lowercase (e.g. `mbedtls_platform_time_alt`).
+const OPTIONS: &[&str] = &[
+    "MBEDTLS_HAVE_TIME",
+    "MBEDTLS_PLATFORM_TIME_ALT",
+    "MBEDTLS_SSL_DTLS_CONNECTION_ID",
+    "MBEDTLS_SSL_DTLS_HELLO_VERIFY",
+    "MBEDTLS_SSL_EARLY_DATA",
+    "MBEDTLS_SSL_KEYING_MATERIAL_EXPORT",
+    "MBEDTLS_SSL_RECORD_SIZE_LIMIT",
+];
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+
+    // Exported by the build script of `esp-mbedtls-sys`
+    let options = env::var("DEP_MBEDTLS_OPTIONS").unwrap_or_default();
+
+    for option in OPTIONS {
+        let cfg = option.to_ascii_lowercase();
+
+        println!("cargo::rustc-check-cfg=cfg({cfg})");
+
+        if options.split(',').any(|enabled| enabled == *option) {
+            println!("cargo::rustc-cfg={cfg}");
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use core::ffi::c_int;
+
+use crate::sys::*;
+
+/// The level of a TLS alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AlertLevel {
+    /// A warning alert; the connection might continue
+    Warning = MBEDTLS_SSL_ALERT_LEVEL_WARNING as u8,
+    /// A fatal alert; the connection is terminated
+    Fatal = MBEDTLS_SSL_ALERT_LEVEL_FATAL as u8,
+}
+
+impl AlertLevel {
+    /// Get the alert level corresponding to the provided code
+    ///
+    /// # Returns
+    /// - The alert level, or `None` if the code is not known
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id as u32 {
+            MBEDTLS_SSL_ALERT_LEVEL_WARNING => Some(Self::Warning),
+            MBEDTLS_SSL_ALERT_LEVEL_FATAL => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    /// Get the code of the alert level
+    pub const fn id(&self) -> u8 {
+        *self as u8
+    }
+}
+
+/// Generates the `AlertDescription` enum from the `MBEDTLS_SSL_ALERT_MSG_*` constants in the bindings
+macro_rules! alert_descriptions {
+    ($($variant:ident = $id:ident => $name:literal,)*) => {
+        /// The description of a TLS alert
+        ///
+        /// The discriminant of each variant is the IANA code of the alert.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u8)]
+        pub enum AlertDescription {
+            $(
+                #[doc = $name]
+                $variant = $id as u8,
+            )*
+        }
+
+        impl AlertDescription {
+            /// Get the alert description corresponding to the provided IANA code
+            ///
+            /// # Returns
+            /// - The alert description, or `None` if the code is not known
+            pub const fn from_id(id: u8) -> Option<Self> {
+                #[allow(non_upper_case_globals)]
+                match id as u32 {
+                    $($id => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Get the IANA code of the alert description
+            pub const fn id(&self) -> u8 {
+                *self as u8
+            }
+
+            /// Get the IANA name of the alert description (e.g. `bad_certificate`)
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+
+        impl core::fmt::Display for AlertDescription {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+    };
+}
+
+alert_descriptions! {
+    CloseNotify = MBEDTLS_SSL_ALERT_MSG_CLOSE_NOTIFY => "close_notify",
+    UnexpectedMessage = MBEDTLS_SSL_ALERT_MSG_UNEXPECTED_MESSAGE => "unexpected_message",
+    BadRecordMac = MBEDTLS_SSL_ALERT_MSG_BAD_RECORD_MAC => "bad_record_mac",
+    DecryptionFailed = MBEDTLS_SSL_ALERT_MSG_DECRYPTION_FAILED => "decryption_failed",
+    RecordOverflow = MBEDTLS_SSL_ALERT_MSG_RECORD_OVERFLOW => "record_overflow",
+    DecompressionFailure = MBEDTLS_SSL_ALERT_MSG_DECOMPRESSION_FAILURE => "decompression_failure",
+    HandshakeFailure = MBEDTLS_SSL_ALERT_MSG_HANDSHAKE_FAILURE => "handshake_failure",
+    NoCertificate = MBEDTLS_SSL_ALERT_MSG_NO_CERT => "no_certificate",
+    BadCertificate = MBEDTLS_SSL_ALERT_MSG_BAD_CERT => "bad_certificate",
+    UnsupportedCertificate = MBEDTLS_SSL_ALERT_MSG_UNSUPPORTED_CERT => "unsupported_certificate",
+    CertificateRevoked = MBEDTLS_SSL_ALERT_MSG_CERT_REVOKED => "certificate_revoked",
+    CertificateExpired = MBEDTLS_SSL_ALERT_MSG_CERT_EXPIRED => "certificate_expired",
+    CertificateUnknown = MBEDTLS_SSL_ALERT_MSG_CERT_UNKNOWN => "certificate_unknown",
+    IllegalParameter = MBEDTLS_SSL_ALERT_MSG_ILLEGAL_PARAMETER => "illegal_parameter",
+    UnknownCa = MBEDTLS_SSL_ALERT_MSG_UNKNOWN_CA => "unknown_ca",
+    AccessDenied = MBEDTLS_SSL_ALERT_MSG_ACCESS_DENIED => "access_denied",
+    DecodeError = MBEDTLS_SSL_ALERT_MSG_DECODE_ERROR => "decode_error",
+    DecryptError = MBEDTLS_SSL_ALERT_MSG_DECRYPT_ERROR => "decrypt_error",
+    ExportRestriction = MBEDTLS_SSL_ALERT_MSG_EXPORT_RESTRICTION => "export_restriction",
+    ProtocolVersion = MBEDTLS_SSL_ALERT_MSG_PROTOCOL_VERSION => "protocol_version",
+    InsufficientSecurity = MBEDTLS_SSL_ALERT_MSG_INSUFFICIENT_SECURITY => "insufficient_security",
+    InternalError = MBEDTLS_SSL_ALERT_MSG_INTERNAL_ERROR => "internal_error",
+    InappropriateFallback = MBEDTLS_SSL_ALERT_MSG_INAPROPRIATE_FALLBACK => "inappropriate_fallback",
+    UserCanceled = MBEDTLS_SSL_ALERT_MSG_USER_CANCELED => "user_canceled",
+    NoRenegotiation = MBEDTLS_SSL_ALERT_MSG_NO_RENEGOTIATION => "no_renegotiation",
+    MissingExtension = MBEDTLS_SSL_ALERT_MSG_MISSING_EXTENSION => "missing_extension",
+    UnsupportedExtension = MBEDTLS_SSL_ALERT_MSG_UNSUPPORTED_EXT => "unsupported_extension",
+    UnrecognizedName = MBEDTLS_SSL_ALERT_MSG_UNRECOGNIZED_NAME => "unrecognized_name",
+    UnknownPskIdentity = MBEDTLS_SSL_ALERT_MSG_UNKNOWN_PSK_IDENTITY => "unknown_psk_identity",
+    CertificateRequired = MBEDTLS_SSL_ALERT_MSG_CERT_REQUIRED => "certificate_required",
+    NoApplicationProtocol = MBEDTLS_SSL_ALERT_MSG_NO_APPLICATION_PROTOCOL => "no_application_protocol",
+}
+
+/// Get the alert last received from the peer, if the last record read by MbedTLS was an alert
+/// with a known level and description
+pub(crate) fn received_alert(
+    ssl_context: &mbedtls_ssl_context,
+) -> Option<(AlertLevel, AlertDescription)> {
+    // MbedTLS has no API for retrieving the received alert, hence the direct field access
+    if ssl_context.private_in_msgtype != MBEDTLS_SSL_MSG_ALERT as c_int
+        || ssl_context.private_in_msg.is_null()
+    {
+        return None;
+    }
+
+    let msg = unsafe { core::slice::from_raw_parts(ssl_context.private_in_msg, 2) };
+
+    Some((
+        AlertLevel::from_id(msg[0])?,
+        AlertDescription::from_id(msg[1])?,
+    ))
+}
@@ -0,0 +1,110 @@
+use crate::sys::*;
+
+/// Generates a `#[repr(u16)]` enum from the IANA code points defined as constants in the bindings
+macro_rules! iana_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $enum:ident {
+            $($variant:ident = $id:ident => $name:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u16)]
+        pub enum $enum {
+            $(
+                #[doc = $name]
+                $variant = $id as u16,
+            )*
+        }
+
+        impl $enum {
+            /// Get the value corresponding to the provided IANA code point
+            ///
+            /// # Returns
+            /// - The value, or `None` if the code point is not known
+            pub const fn from_id(id: u16) -> Option<Self> {
+                #[allow(non_upper_case_globals)]
+                match id as u32 {
+                    $($id => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Get the IANA code point
+            pub const fn id(&self) -> u16 {
+                *self as u16
+            }
+
+            /// Get the IANA name (e.g. `x25519` or `ecdsa_secp256r1_sha256`)
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+
+        impl core::fmt::Display for $enum {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
+    };
+}
+
+iana_enum! {
+    /// A named group (elliptic curve or finite field group) used for the (EC)DHE key exchange
+    ///
+    /// The discriminant of each variant is the IANA ID of the group.
+    ///
+    /// Note that not all groups listed here are necessarily compiled into MbedTLS.
+    pub enum NamedGroup {
+        Secp192k1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP192K1 => "secp192k1",
+        Secp192r1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP192R1 => "secp192r1",
+        Secp224k1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP224K1 => "secp224k1",
+        Secp224r1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP224R1 => "secp224r1",
+        Secp256k1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP256K1 => "secp256k1",
+        Secp256r1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP256R1 => "secp256r1",
+        Secp384r1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP384R1 => "secp384r1",
+        Secp521r1 = MBEDTLS_SSL_IANA_TLS_GROUP_SECP521R1 => "secp521r1",
+        BrainpoolP256r1 = MBEDTLS_SSL_IANA_TLS_GROUP_BP256R1 => "brainpoolP256r1",
+        BrainpoolP384r1 = MBEDTLS_SSL_IANA_TLS_GROUP_BP384R1 => "brainpoolP384r1",
+        BrainpoolP512r1 = MBEDTLS_SSL_IANA_TLS_GROUP_BP512R1 => "brainpoolP512r1",
+        X25519 = MBEDTLS_SSL_IANA_TLS_GROUP_X25519 => "x25519",
+        X448 = MBEDTLS_SSL_IANA_TLS_GROUP_X448 => "x448",
+        Ffdhe2048 = MBEDTLS_SSL_IANA_TLS_GROUP_FFDHE2048 => "ffdhe2048",
+        Ffdhe3072 = MBEDTLS_SSL_IANA_TLS_GROUP_FFDHE3072 => "ffdhe3072",
+        Ffdhe4096 = MBEDTLS_SSL_IANA_TLS_GROUP_FFDHE4096 => "ffdhe4096",
+        Ffdhe6144 = MBEDTLS_SSL_IANA_TLS_GROUP_FFDHE6144 => "ffdhe6144",
+        Ffdhe8192 = MBEDTLS_SSL_IANA_TLS_GROUP_FFDHE8192 => "ffdhe8192",
+    }
+}
+
+iana_enum! {
+    /// A signature algorithm (TLS 1.3 `SignatureScheme`)
+    ///
+    /// The discriminant of each variant is the IANA ID of the signature scheme.
+    /// MbedTLS uses the TLS 1.3 code points for TLS 1.2 as well, where they map onto the
+    /// corresponding hash/signature algorithm pairs.
+    ///
+    /// Note that not all signature algorithms listed here are necessarily compiled into MbedTLS.
+    pub enum SignatureAlgorithm {
+        RsaPkcs1Sha1 = MBEDTLS_TLS1_3_SIG_RSA_PKCS1_SHA1 => "rsa_pkcs1_sha1",
+        EcdsaSha1 = MBEDTLS_TLS1_3_SIG_ECDSA_SHA1 => "ecdsa_sha1",
+        RsaPkcs1Sha256 = MBEDTLS_TLS1_3_SIG_RSA_PKCS1_SHA256 => "rsa_pkcs1_sha256",
+        RsaPkcs1Sha384 = MBEDTLS_TLS1_3_SIG_RSA_PKCS1_SHA384 => "rsa_pkcs1_sha384",
+        RsaPkcs1Sha512 = MBEDTLS_TLS1_3_SIG_RSA_PKCS1_SHA512 => "rsa_pkcs1_sha512",
+        EcdsaSecp256r1Sha256 = MBEDTLS_TLS1_3_SIG_ECDSA_SECP256R1_SHA256 => "ecdsa_secp256r1_sha256",
+        EcdsaSecp384r1Sha384 = MBEDTLS_TLS1_3_SIG_ECDSA_SECP384R1_SHA384 => "ecdsa_secp384r1_sha384",
+        EcdsaSecp521r1Sha512 = MBEDTLS_TLS1_3_SIG_ECDSA_SECP521R1_SHA512 => "ecdsa_secp521r1_sha512",
+        RsaPssRsaeSha256 = MBEDTLS_TLS1_3_SIG_RSA_PSS_RSAE_SHA256 => "rsa_pss_rsae_sha256",
+        RsaPssRsaeSha384 = MBEDTLS_TLS1_3_SIG_RSA_PSS_RSAE_SHA384 => "rsa_pss_rsae_sha384",
+        RsaPssRsaeSha512 = MBEDTLS_TLS1_3_SIG_RSA_PSS_RSAE_SHA512 => "rsa_pss_rsae_sha512",
+        Ed25519 = MBEDTLS_TLS1_3_SIG_ED25519 => "ed25519",
+        Ed448 = MBEDTLS_TLS1_3_SIG_ED448 => "ed448",
+        RsaPssPssSha256 = MBEDTLS_TLS1_3_SIG_RSA_PSS_PSS_SHA256 => "rsa_pss_pss_sha256",
+        RsaPssPssSha384 = MBEDTLS_TLS1_3_SIG_RSA_PSS_PSS_SHA384 => "rsa_pss_pss_sha384",
+        RsaPssPssSha512 = MBEDTLS_TLS1_3_SIG_RSA_PSS_PSS_SHA512 => "rsa_pss_pss_sha512",
+    }
+}
@@ -0,0 +1,212 @@
+use core::ffi::{c_int, c_uchar, c_void};
+use core::mem::size_of;
+
+use super::sys::*;
+use super::{MInit, MRc};
+
+/// A server-side session cache, enabling session ID-based resumption of TLS 1.2 sessions
+/// (e.g. for legacy clients which do not support session tickets).
+///
+/// Instances are cheap to clone and all clones share the same cache, so that a single instance
+/// can be plugged into the `ServerSessionConfig` used by e.g. `TlsAcceptor`, and
+/// the sessions negotiated by any of the connections can be resumed by all others.
+///
+/// The cache memory is allocated on the heap, one entry per cached session. Each entry takes
+/// [SESSION_CACHE_ENTRY_OVERHEAD] bytes plus the serialized session, which is a couple hundred bytes,
+/// unless client authentication is used, in which case it also contains the complete client certificate.
+/// Sessions whose serialized size exceeds the maximum entry size are not cached (and thus cannot be resumed),
+/// and once the cache is full, the oldest entry is replaced, so the cache memory never exceeds
+/// `max_entries * (SESSION_CACHE_ENTRY_OVERHEAD + max_entry_size)` bytes, plus one serialized session
+/// while a new session is being cached. The current usage can be queried with [SessionCache::memory_usage].
+#[derive(Clone)]
+pub struct SessionCache {
+    pub(crate) ctx: MRc<SessionCacheContext>,
+}
+
+/// The fixed heap memory taken by a session cache entry, in addition to the serialized session itself
+pub const SESSION_CACHE_ENTRY_OVERHEAD: usize = size_of::<mbedtls_ssl_cache_entry>();
+
+impl SessionCache {
+    /// Create a new, empty session cache
+    ///
+    /// # Arguments
+    /// - `max_entries` - The maximum number of cached sessions. Must be greater than 0.
+    /// - `max_entry_size` - The maximum size of a serialized session, in bytes. Larger sessions are not cached.
+    ///   Note that with client authentication, the serialized sessions contain the client certificates.
+    /// - `timeout` - The time after which a cached session expires, in seconds. Note that the sessions
+    ///   can only expire if MbedTLS is built with `MBEDTLS_HAVE_TIME` and has access to the current time.
+    pub fn new(
+        max_entries: u16,
+        max_entry_size: usize,
+        timeout: u32,
+    ) -> Result<Self, MbedtlsError> {
+        if max_entries == 0 {
+            return Err(MbedtlsError::new(MBEDTLS_ERR_SSL_BAD_INPUT_DATA));
+        }
+
+        let ctx = MRc::<SessionCacheContext>::new()
+            .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        let raw = &*ctx as *const _ as *mut SessionCacheContext;
+
+        unsafe {
+            (*raw).max_entry_size = max_entry_size;
+
+            mbedtls_ssl_cache_set_max_entries(&mut (*raw).cache, max_entries as c_int);
+
+            #[cfg(mbedtls_have_time)]
+            mbedtls_ssl_cache_set_timeout(&mut (*raw).cache, timeout.min(c_int::MAX as u32) as _);
+        }
+
+        #[cfg(not(mbedtls_have_time))]
+        let _ = timeout;
+
+        Ok(Self { ctx })
+    }
+
+    /// Get the maximum number of cached sessions
+    pub fn max_entries(&self) -> usize {
+        self.ctx.cache.private_max_entries as usize
+    }
+
+    /// Get the maximum size of a cached serialized session, in bytes
+    pub fn max_entry_size(&self) -> usize {
+        self.ctx.max_entry_size
+    }
+
+    /// Get the time after which a cached session expires, in seconds
+    pub fn timeout(&self) -> u32 {
+        self.ctx.cache.private_timeout as u32
+    }
+
+    /// Get the number of currently cached sessions
+    pub fn len(&self) -> usize {
+        self.entries().count()
+    }
+
+    /// Return `true` if no sessions are currently cached
+    pub fn is_empty(&self) -> bool {
+        self.ctx.cache.private_chain.is_null()
+    }
+
+    /// Get the heap memory currently taken by the cached sessions, in bytes
+    ///
+    /// See [SessionCache] for the bound of the memory usage.
+    pub fn memory_usage(&self) -> usize {
+        self.entries()
+            .map(|entry| SESSION_CACHE_ENTRY_OVERHEAD + entry.private_session_len)
+            .sum()
+    }
+
+    /// Get a raw pointer to the cache context, to be used as the MbedTLS cache callbacks' context
+    pub(crate) fn as_ptr(&self) -> *mut c_void {
+        &*self.ctx as *const _ as *mut c_void
+    }
+
+    /// Iterate over the cache entries
+    fn entries(&self) -> impl Iterator<Item = &mbedtls_ssl_cache_entry> {
+        core::iter::successors(
+            unsafe { self.ctx.cache.private_chain.as_ref() },
+            |entry| unsafe { entry.private_next.as_ref() },
+        )
+    }
+}
+
+impl core::fmt::Debug for SessionCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionCache")
+            .field("max_entries", &self.max_entries())
+            .field("max_entry_size", &self.max_entry_size())
+            .field("timeout", &self.timeout())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SessionCache {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "SessionCache {{ max_entries: {}, max_entry_size: {}, timeout: {}, len: {} }}",
+            self.max_entries(),
+            self.max_entry_size(),
+            self.timeout(),
+            self.len()
+        )
+    }
+}
+
+/// The context of the session cache callbacks
+pub(crate) struct SessionCacheContext {
+    /// The MbedTLS session cache
+    cache: mbedtls_ssl_cache_context,
+    /// The maximum size of a cached serialized session, in bytes
+    max_entry_size: usize,
+}
+
+impl MInit for SessionCacheContext {
+    fn init(&mut self) {
+        unsafe {
+            mbedtls_ssl_cache_init(&mut self.cache);
+        }
+    }
+
+    fn deinit(&mut self) {
+        unsafe {
+            mbedtls_ssl_cache_free(&mut self.cache);
+        }
+    }
+}
+
+/// The MbedTLS session cache get callback, looking up a cached session by ID
+pub(crate) unsafe extern "C" fn session_cache_get(
+    data: *mut c_void,
+    session_id: *const c_uchar,
+    session_id_len: usize,
+    session: *mut mbedtls_ssl_session,
+) -> c_int {
+    let ctx = data as *mut SessionCacheContext;
+
+    mbedtls_ssl_cache_get(
+        &mut (*ctx).cache as *mut _ as *mut c_void,
+        session_id,
+        session_id_len,
+        session,
+    )
+}
+
+/// The MbedTLS session cache set callback, caching a session unless its serialized size
+/// exceeds the maximum entry size
+pub(crate) unsafe extern "C" fn session_cache_set(
+    data: *mut c_void,
+    session_id: *const c_uchar,
+    session_id_len: usize,
+    session: *const mbedtls_ssl_session,
+) -> c_int {
+    let ctx = data as *mut SessionCacheContext;
+
+    // Same as `mbedtls_ssl_cache_set`, which serializes the session with a buffer of the size computed this way
+    let mut len = 0;
+    let ret = mbedtls_ssl_session_save(session, core::ptr::null_mut(), 0, &mut len);
+    if ret != MBEDTLS_ERR_SSL_BUFFER_TOO_SMALL {
+        return ret;
+    }
+
+    if len > (*ctx).max_entry_size {
+        debug!(
+            "Not caching a session of {} bytes, as it exceeds the maximum entry size of {} bytes",
+            len,
+            (*ctx).max_entry_size
+        );
+
+        return MBEDTLS_ERR_SSL_BAD_INPUT_DATA;
+    }
+
+    mbedtls_ssl_cache_set(
+        &mut (*ctx).cache as *mut _ as *mut c_void,
+        session_id,
+        session_id_len,
+        session,
+    )
+}
@@ -1,5 +1,8 @@
-use core::ffi::CStr;
+use core::ffi::{c_char, CStr};
 use core::marker::PhantomData;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use enumset::{EnumSet, EnumSetType};
 
 use super::sys::*;
 use super::{MRc, SessionError};
@@ -94,6 +97,671 @@ impl<'d> Certificate<'d> {
             _t: PhantomData,
         })
     }
+
+    /// Iterate over the certificates of the chain, in the order in which they were parsed
+    pub fn chain(&self) -> impl Iterator<Item = CertificateRef<'_>> {
+        // A certificate version of 0 means that the chain is empty
+        let first = (self.crt.version != 0).then(|| CertificateRef::new(&self.crt));
+
+        core::iter::successors(first, CertificateRef::next_in_chain)
+    }
+}
+
+/// A parsed X509 certificate revocation list (CRL), or a list of CRLs.
+///
+/// Used along with a CA chain, so that certificates revoked by the CAs are rejected
+/// (with `MBEDTLS_X509_BADCERT_REVOKED` set in the verification flags).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Crl<'d> {
+    pub(crate) crl: MRc<mbedtls_x509_crl>,
+    _t: PhantomData<&'d ()>,
+}
+
+impl Crl<'static> {
+    /// Parse an X509 CRL into RAM by making a copy
+    ///
+    /// Note that unlike certificates, CRLs are always copied, as MbedTLS does not support
+    /// parsing them in-place.
+    ///
+    /// # Arguments
+    ///
+    /// * `crl` - The X509 CRL in PEM or DER format. A PEM input may contain multiple CRLs.
+    ///
+    /// # Errors
+    ///
+    /// This will return an error if an error occurs during parsing such as passing a DER encoded
+    /// CRL in a PEM format, and vice-versa.
+    pub fn new(x509: X509<'_>) -> Result<Self, MbedtlsError> {
+        let crl = MRc::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_X509_ALLOC_FAILED))?;
+
+        match x509 {
+            X509::PEM(str) => merr!(unsafe {
+                mbedtls_x509_crl_parse(
+                    &*crl as *const _ as *mut _,
+                    str.as_ptr() as *const _,
+                    str.count_bytes() + 1,
+                )
+            }),
+            X509::DER(bytes) => merr!(unsafe {
+                mbedtls_x509_crl_parse_der(&*crl as *const _ as *mut _, bytes.as_ptr(), bytes.len())
+            }),
+        }?;
+
+        Ok(Self {
+            crl,
+            _t: PhantomData,
+        })
+    }
+}
+
+impl Crl<'_> {
+    /// Check whether the provided certificate is revoked by this CRL
+    ///
+    /// Note that only the serial number of the certificate is checked,
+    /// i.e. the CRL is assumed to be issued by the issuer of the certificate.
+    pub fn is_revoked(&self, cert: CertificateRef<'_>) -> bool {
+        unsafe { mbedtls_x509_crt_is_revoked(cert.raw(), &*self.crl) != 0 }
+    }
+}
+
+/// A reference to a parsed X509 certificate owned by MbedTLS
+/// (e.g. one of the certificates of a chain being verified)
+#[derive(Debug, Clone, Copy)]
+pub struct CertificateRef<'a>(&'a mbedtls_x509_crt);
+
+impl<'a> CertificateRef<'a> {
+    /// Create a certificate reference out of an MbedTLS certificate
+    pub(crate) const fn new(crt: &'a mbedtls_x509_crt) -> Self {
+        Self(crt)
+    }
+
+    /// Get the DER encoding of the certificate
+    pub fn der(&self) -> &'a [u8] {
+        buf(&self.0.raw)
+    }
+
+    /// Get the raw MbedTLS certificate, for accessing the fields not exposed by this type
+    pub fn raw(&self) -> &'a mbedtls_x509_crt {
+        self.0
+    }
+
+    /// Get the next certificate of the chain this certificate is part of, if any
+    pub fn next_in_chain(&self) -> Option<Self> {
+        unsafe { self.0.next.as_ref() }
+            .filter(|crt| crt.version != 0)
+            .map(Self)
+    }
+
+    /// Iterate over the certificates of the chain, starting with this certificate
+    pub fn chain(&self) -> impl Iterator<Item = CertificateRef<'a>> {
+        core::iter::successors(Some(*self), Self::next_in_chain)
+    }
+
+    /// Get the X509 version of the certificate (1, 2 or 3)
+    pub fn version(&self) -> u8 {
+        self.0.version as u8
+    }
+
+    /// Get the serial number of the certificate, as big-endian bytes
+    pub fn serial(&self) -> &'a [u8] {
+        buf(&self.0.serial)
+    }
+
+    /// Get the subject of the certificate
+    pub fn subject(&self) -> DistinguishedName<'a> {
+        DistinguishedName(&self.0.subject)
+    }
+
+    /// Get the issuer of the certificate
+    pub fn issuer(&self) -> DistinguishedName<'a> {
+        DistinguishedName(&self.0.issuer)
+    }
+
+    /// Get the start of the validity period of the certificate (`notBefore`), in UTC
+    pub fn not_before(&self) -> mbedtls_x509_time {
+        self.0.valid_from
+    }
+
+    /// Get the end of the validity period of the certificate (`notAfter`), in UTC
+    pub fn not_after(&self) -> mbedtls_x509_time {
+        self.0.valid_to
+    }
+
+    /// Iterate over the subject alternative names of the certificate
+    pub fn subject_alt_names(&self) -> impl Iterator<Item = SubjectAltName<'a>> {
+        sequence(&self.0.subject_alt_names).map(SubjectAltName::new)
+    }
+
+    /// Get the type of the public key of the certificate
+    pub fn key_type(&self) -> KeyType {
+        KeyType::new(unsafe { mbedtls_pk_get_type(&self.0.pk) })
+    }
+
+    /// Get the size of the public key of the certificate, in bits
+    pub fn key_bits(&self) -> usize {
+        unsafe { mbedtls_pk_get_bitlen(&self.0.pk) }
+    }
+
+    /// Get the key usage of the certificate
+    ///
+    /// # Returns
+    /// - The key usage, or `None` if the certificate does not have a key usage extension
+    pub fn key_usage(&self) -> Option<EnumSet<KeyUsage>> {
+        self.has_ext(MBEDTLS_OID_X509_EXT_KEY_USAGE)
+            .then(|| KeyUsage::from_bits(self.0.private_key_usage))
+    }
+
+    /// Iterate over the extended key usage purposes of the certificate
+    ///
+    /// The iterator is empty if the certificate does not have an extended key usage extension.
+    pub fn extended_key_usage(&self) -> impl Iterator<Item = ExtendedKeyUsage<'a>> {
+        sequence(&self.0.ext_key_usage).map(ExtendedKeyUsage::new)
+    }
+
+    /// Get the basic constraints of the certificate
+    ///
+    /// # Returns
+    /// - The basic constraints, or `None` if the certificate does not have a basic constraints extension
+    pub fn basic_constraints(&self) -> Option<BasicConstraints> {
+        self.has_ext(MBEDTLS_OID_X509_EXT_BASIC_CONSTRAINTS)
+            .then(|| BasicConstraints {
+                ca: self.0.private_ca_istrue != 0,
+                // MbedTLS stores the path length incremented by one, with 0 meaning unlimited
+                path_len: (self.0.private_max_pathlen > 0)
+                    .then(|| self.0.private_max_pathlen as u32 - 1),
+            })
+    }
+
+    /// Get the SHA-256 fingerprint of the certificate, i.e. the hash of its DER encoding
+    pub fn fingerprint(&self) -> [u8; 32] {
+        sha256(&self.0.raw)
+    }
+
+    /// Get the SHA-256 hash of the DER-encoded SubjectPublicKeyInfo of the certificate
+    pub fn spki_fingerprint(&self) -> [u8; 32] {
+        sha256(&self.0.pk_raw)
+    }
+
+    /// Check whether the certificate contains the provided extension (`MBEDTLS_OID_X509_EXT_*`)
+    fn has_ext(&self, ext: u32) -> bool {
+        self.0.private_ext_types as u32 & ext != 0
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CertificateRef<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "CertificateRef {{ der: {=[u8]:02x} }}", self.der())
+    }
+}
+
+/// A certificate verification failure flag (`MBEDTLS_X509_BADCERT_*` and `MBEDTLS_X509_BADCRL_*`)
+///
+/// The discriminant of each variant is the position of the corresponding MbedTLS flag bit.
+#[derive(EnumSetType, Debug)]
+#[enumset(repr = "u32")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VerifyFlag {
+    /// The certificate validity period has expired
+    Expired = 0,
+    /// The certificate has been revoked (is on a CRL)
+    Revoked = 1,
+    /// The certificate Common Name (CN) or Subject Alternative Names do not match the expected server name
+    CnMismatch = 2,
+    /// The certificate is not correctly signed by a trusted CA
+    NotTrusted = 3,
+    /// The CRL is not correctly signed by the trusted CA
+    CrlNotTrusted = 4,
+    /// The CRL is expired
+    CrlExpired = 5,
+    /// The certificate was missing
+    Missing = 6,
+    /// The certificate verification was skipped
+    SkipVerify = 7,
+    /// Other reason (e.g. rejected by a custom verification hook)
+    Other = 8,
+    /// The certificate validity starts in the future
+    Future = 9,
+    /// The CRL is from the future
+    CrlFuture = 10,
+    /// The certificate key usage does not match its purpose
+    KeyUsage = 11,
+    /// The certificate extended key usage does not match its purpose
+    ExtKeyUsage = 12,
+    /// The certificate Netscape certificate type does not match its purpose
+    NsCertType = 13,
+    /// The certificate is signed with an unacceptable hash
+    BadMd = 14,
+    /// The certificate is signed with an unacceptable public key algorithm (e.g. RSA vs ECDSA)
+    BadPk = 15,
+    /// The certificate is signed with an unacceptable key (e.g. bad curve, RSA too short)
+    BadKey = 16,
+    /// The CRL is signed with an unacceptable hash
+    CrlBadMd = 17,
+    /// The CRL is signed with an unacceptable public key algorithm (e.g. RSA vs ECDSA)
+    CrlBadPk = 18,
+    /// The CRL is signed with an unacceptable key (e.g. bad curve, RSA too short)
+    CrlBadKey = 19,
+}
+
+// The discriminants of `VerifyFlag` are hard-coded, so check them against the MbedTLS flags
+const _: () = {
+    core::assert!(VerifyFlag::Expired.bit() == MBEDTLS_X509_BADCERT_EXPIRED);
+    core::assert!(VerifyFlag::Revoked.bit() == MBEDTLS_X509_BADCERT_REVOKED);
+    core::assert!(VerifyFlag::CnMismatch.bit() == MBEDTLS_X509_BADCERT_CN_MISMATCH);
+    core::assert!(VerifyFlag::NotTrusted.bit() == MBEDTLS_X509_BADCERT_NOT_TRUSTED);
+    core::assert!(VerifyFlag::CrlNotTrusted.bit() == MBEDTLS_X509_BADCRL_NOT_TRUSTED);
+    core::assert!(VerifyFlag::CrlExpired.bit() == MBEDTLS_X509_BADCRL_EXPIRED);
+    core::assert!(VerifyFlag::Missing.bit() == MBEDTLS_X509_BADCERT_MISSING);
+    core::assert!(VerifyFlag::SkipVerify.bit() == MBEDTLS_X509_BADCERT_SKIP_VERIFY);
+    core::assert!(VerifyFlag::Other.bit() == MBEDTLS_X509_BADCERT_OTHER);
+    core::assert!(VerifyFlag::Future.bit() == MBEDTLS_X509_BADCERT_FUTURE);
+    core::assert!(VerifyFlag::CrlFuture.bit() == MBEDTLS_X509_BADCRL_FUTURE);
+    core::assert!(VerifyFlag::KeyUsage.bit() == MBEDTLS_X509_BADCERT_KEY_USAGE);
+    core::assert!(VerifyFlag::ExtKeyUsage.bit() == MBEDTLS_X509_BADCERT_EXT_KEY_USAGE);
+    core::assert!(VerifyFlag::NsCertType.bit() == MBEDTLS_X509_BADCERT_NS_CERT_TYPE);
+    core::assert!(VerifyFlag::BadMd.bit() == MBEDTLS_X509_BADCERT_BAD_MD);
+    core::assert!(VerifyFlag::BadPk.bit() == MBEDTLS_X509_BADCERT_BAD_PK);
+    core::assert!(VerifyFlag::BadKey.bit() == MBEDTLS_X509_BADCERT_BAD_KEY);
+    core::assert!(VerifyFlag::CrlBadMd.bit() == MBEDTLS_X509_BADCRL_BAD_MD);
+    core::assert!(VerifyFlag::CrlBadPk.bit() == MBEDTLS_X509_BADCRL_BAD_PK);
+    core::assert!(VerifyFlag::CrlBadKey.bit() == MBEDTLS_X509_BADCRL_BAD_KEY);
+};
+
+impl VerifyFlag {
+    /// Get the MbedTLS `MBEDTLS_X509_BADCERT_*` or `MBEDTLS_X509_BADCRL_*` bit of the flag
+    pub const fn bit(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    /// Get the set of flags corresponding to an MbedTLS verification result bitmask
+    ///
+    /// Unknown bits are ignored.
+    pub fn from_bits(bits: u32) -> EnumSet<Self> {
+        EnumSet::from_repr_truncated(bits)
+    }
+
+    /// Get the MbedTLS verification result bitmask corresponding to a set of flags
+    pub fn to_bits(flags: EnumSet<Self>) -> u32 {
+        flags.as_repr()
+    }
+
+    /// Get a human-readable description of the flag
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::Expired => "The certificate validity has expired",
+            Self::Revoked => "The certificate has been revoked (is on a CRL)",
+            Self::CnMismatch => {
+                "The certificate Common Name (CN) does not match with the expected CN"
+            }
+            Self::NotTrusted => "The certificate is not correctly signed by the trusted CA",
+            Self::CrlNotTrusted => "The CRL is not correctly signed by the trusted CA",
+            Self::CrlExpired => "The CRL is expired",
+            Self::Missing => "Certificate was missing",
+            Self::SkipVerify => "Certificate verification was skipped",
+            Self::Other => "Other reason (can be used by verify callback)",
+            Self::Future => "The certificate validity starts in the future",
+            Self::CrlFuture => "The CRL is from the future",
+            Self::KeyUsage => "Usage does not match the keyUsage extension",
+            Self::ExtKeyUsage => "Usage does not match the extendedKeyUsage extension",
+            Self::NsCertType => "Usage does not match the nsCertType extension",
+            Self::BadMd => "The certificate is signed with an unacceptable hash",
+            Self::BadPk => {
+                "The certificate is signed with an unacceptable PK alg (eg RSA vs ECDSA)"
+            }
+            Self::BadKey => {
+                "The certificate is signed with an unacceptable key (eg bad curve, RSA too short)"
+            }
+            Self::CrlBadMd => "The CRL is signed with an unacceptable hash",
+            Self::CrlBadPk => "The CRL is signed with an unacceptable PK alg (eg RSA vs ECDSA)",
+            Self::CrlBadKey => {
+                "The CRL is signed with an unacceptable key (eg bad curve, RSA too short)"
+            }
+        }
+    }
+}
+
+impl core::fmt::Display for VerifyFlag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.description())
+    }
+}
+
+/// A SHA-256 pin of a peer certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CertificatePin {
+    /// SHA-256 hash of the DER-encoded SubjectPublicKeyInfo of the certificate
+    ///
+    /// Unlike [CertificatePin::Certificate], survives certificate renewals which keep the key pair.
+    Spki([u8; 32]),
+    /// SHA-256 hash of the whole DER-encoded certificate
+    Certificate([u8; 32]),
+}
+
+impl CertificatePin {
+    /// Check whether the provided certificate matches this pin
+    pub fn matches(&self, cert: CertificateRef<'_>) -> bool {
+        match self {
+            Self::Spki(pin) => cert.spki_fingerprint() == *pin,
+            Self::Certificate(pin) => cert.fingerprint() == *pin,
+        }
+    }
+}
+
+/// A distinguished name (the subject or issuer of a certificate)
+#[derive(Clone, Copy)]
+pub struct DistinguishedName<'a>(&'a mbedtls_x509_name);
+
+impl<'a> DistinguishedName<'a> {
+    /// Iterate over the attributes of the name, in the order in which they appear in the certificate
+    pub fn attributes(&self) -> impl Iterator<Item = NameAttribute<'a>> {
+        core::iter::successors(Some(self.0), |name| unsafe { name.next.as_ref() })
+            .filter(|name| !name.oid.p.is_null())
+            .map(|name| NameAttribute {
+                oid: buf(&name.oid),
+                value: buf(&name.val),
+            })
+    }
+
+    /// Get the value of the first attribute with the provided short name (e.g. `CN`, `O` or `C`), if any
+    pub fn get(&self, short_name: &str) -> Option<&'a [u8]> {
+        self.attributes()
+            .find(|attr| attr.short_name() == Some(short_name))
+            .map(|attr| attr.value)
+    }
+
+    /// Get the common name (`CN`), if any
+    pub fn common_name(&self) -> Option<&'a str> {
+        self.get("CN")
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    /// Get the organization (`O`), if any
+    pub fn organization(&self) -> Option<&'a str> {
+        self.get("O")
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    /// Get the organizational unit (`OU`), if any
+    pub fn organizational_unit(&self) -> Option<&'a str> {
+        self.get("OU")
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+
+    /// Get the country (`C`), if any
+    pub fn country(&self) -> Option<&'a str> {
+        self.get("C")
+            .and_then(|value| core::str::from_utf8(value).ok())
+    }
+}
+
+impl core::fmt::Display for DistinguishedName<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (index, attr) in self.attributes().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{attr}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl core::fmt::Debug for DistinguishedName<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DistinguishedName({self})")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DistinguishedName<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "DistinguishedName({})", defmt::Display2Format(self))
+    }
+}
+
+/// An attribute of a distinguished name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NameAttribute<'a> {
+    /// The DER-encoded OID of the attribute type
+    pub oid: &'a [u8],
+    /// The value of the attribute, usually a UTF-8 or ASCII string
+    pub value: &'a [u8],
+}
+
+impl NameAttribute<'_> {
+    /// Get the short name of the attribute type (e.g. `CN`, `O` or `C`), if known to MbedTLS
+    pub fn short_name(&self) -> Option<&'static str> {
+        let oid = mbedtls_asn1_buf {
+            tag: MBEDTLS_ASN1_OID as _,
+            len: self.oid.len(),
+            p: self.oid.as_ptr() as *mut _,
+        };
+
+        let mut name: *const c_char = core::ptr::null();
+
+        if unsafe { mbedtls_oid_get_attr_short_name(&oid, &mut name) } != 0 || name.is_null() {
+            return None;
+        }
+
+        unsafe { CStr::from_ptr(name) }.to_str().ok()
+    }
+}
+
+impl core::fmt::Display for NameAttribute<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.short_name() {
+            Some(name) => write!(f, "{name}=")?,
+            None => write!(f, "{:02x?}=", self.oid)?,
+        }
+
+        match core::str::from_utf8(self.value) {
+            Ok(value) => write!(f, "{value}"),
+            Err(_) => write!(f, "{:02x?}", self.value),
+        }
+    }
+}
+
+/// A subject alternative name (SAN) of a certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SubjectAltName<'a> {
+    /// A DNS name, possibly with a wildcard (e.g. `*.example.com`)
+    Dns(&'a str),
+    /// An IP address
+    Ip(IpAddr),
+    /// A URI
+    Uri(&'a str),
+    /// An e-mail address (RFC 822 name)
+    Email(&'a str),
+    /// Any other kind of name (`MBEDTLS_X509_SAN_*`), with its raw DER-encoded value
+    Other { kind: u8, value: &'a [u8] },
+}
+
+impl<'a> SubjectAltName<'a> {
+    fn new(raw: &'a mbedtls_x509_buf) -> Self {
+        let kind = (raw.tag as u32 & MBEDTLS_ASN1_TAG_VALUE_MASK) as u8;
+        let value = buf(raw);
+
+        let str = || core::str::from_utf8(value).ok();
+
+        let name = match kind as u32 {
+            MBEDTLS_X509_SAN_DNS_NAME => str().map(Self::Dns),
+            MBEDTLS_X509_SAN_UNIFORM_RESOURCE_IDENTIFIER => str().map(Self::Uri),
+            MBEDTLS_X509_SAN_RFC822_NAME => str().map(Self::Email),
+            MBEDTLS_X509_SAN_IP_ADDRESS => match value.len() {
+                4 => <[u8; 4]>::try_from(value)
+                    .ok()
+                    .map(|ip| Self::Ip(IpAddr::V4(Ipv4Addr::from(ip)))),
+                16 => <[u8; 16]>::try_from(value)
+                    .ok()
+                    .map(|ip| Self::Ip(IpAddr::V6(Ipv6Addr::from(ip)))),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        name.unwrap_or(Self::Other { kind, value })
+    }
+}
+
+/// The type of a public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyType {
+    /// An RSA key
+    Rsa,
+    /// An elliptic curve key
+    Ec,
+    /// Any other key type
+    Other(mbedtls_pk_type_t),
+}
+
+impl KeyType {
+    #[allow(non_upper_case_globals)]
+    fn new(pk_type: mbedtls_pk_type_t) -> Self {
+        match pk_type {
+            mbedtls_pk_type_t_MBEDTLS_PK_RSA
+            | mbedtls_pk_type_t_MBEDTLS_PK_RSA_ALT
+            | mbedtls_pk_type_t_MBEDTLS_PK_RSASSA_PSS => Self::Rsa,
+            mbedtls_pk_type_t_MBEDTLS_PK_ECKEY
+            | mbedtls_pk_type_t_MBEDTLS_PK_ECKEY_DH
+            | mbedtls_pk_type_t_MBEDTLS_PK_ECDSA => Self::Ec,
+            other => Self::Other(other),
+        }
+    }
+}
+
+/// A key usage of a certificate, as per the key usage extension
+#[derive(EnumSetType, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum KeyUsage {
+    DigitalSignature,
+    NonRepudiation,
+    KeyEncipherment,
+    DataEncipherment,
+    KeyAgreement,
+    KeyCertSign,
+    CrlSign,
+    EncipherOnly,
+    DecipherOnly,
+}
+
+impl KeyUsage {
+    /// Get the MbedTLS `MBEDTLS_X509_KU_*` bit of the key usage
+    pub const fn bit(&self) -> u32 {
+        match self {
+            Self::DigitalSignature => MBEDTLS_X509_KU_DIGITAL_SIGNATURE,
+            Self::NonRepudiation => MBEDTLS_X509_KU_NON_REPUDIATION,
+            Self::KeyEncipherment => MBEDTLS_X509_KU_KEY_ENCIPHERMENT,
+            Self::DataEncipherment => MBEDTLS_X509_KU_DATA_ENCIPHERMENT,
+            Self::KeyAgreement => MBEDTLS_X509_KU_KEY_AGREEMENT,
+            Self::KeyCertSign => MBEDTLS_X509_KU_KEY_CERT_SIGN,
+            Self::CrlSign => MBEDTLS_X509_KU_CRL_SIGN,
+            Self::EncipherOnly => MBEDTLS_X509_KU_ENCIPHER_ONLY,
+            Self::DecipherOnly => MBEDTLS_X509_KU_DECIPHER_ONLY,
+        }
+    }
+
+    fn from_bits(bits: u32) -> EnumSet<Self> {
+        EnumSet::<Self>::all()
+            .iter()
+            .filter(|usage| bits & usage.bit() != 0)
+            .collect()
+    }
+}
+
+/// An extended key usage purpose of a certificate, as per the extended key usage extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ExtendedKeyUsage<'a> {
+    /// TLS server authentication
+    ServerAuth,
+    /// TLS client authentication
+    ClientAuth,
+    /// Code signing
+    CodeSigning,
+    /// E-mail protection
+    EmailProtection,
+    /// Time stamping
+    TimeStamping,
+    /// OCSP response signing
+    OcspSigning,
+    /// Any purpose
+    Any,
+    /// Any other purpose, with its DER-encoded OID
+    Other(&'a [u8]),
+}
+
+impl<'a> ExtendedKeyUsage<'a> {
+    fn new(raw: &'a mbedtls_x509_buf) -> Self {
+        // The MbedTLS OID constants are NUL-terminated
+        let oid = buf(raw);
+        let is = |expected: &[u8]| oid == &expected[..expected.len() - 1];
+
+        if is(MBEDTLS_OID_SERVER_AUTH) {
+            Self::ServerAuth
+        } else if is(MBEDTLS_OID_CLIENT_AUTH) {
+            Self::ClientAuth
+        } else if is(MBEDTLS_OID_CODE_SIGNING) {
+            Self::CodeSigning
+        } else if is(MBEDTLS_OID_EMAIL_PROTECTION) {
+            Self::EmailProtection
+        } else if is(MBEDTLS_OID_TIME_STAMPING) {
+            Self::TimeStamping
+        } else if is(MBEDTLS_OID_OCSP_SIGNING) {
+            Self::OcspSigning
+        } else if is(MBEDTLS_OID_ANY_EXTENDED_KEY_USAGE) {
+            Self::Any
+        } else {
+            Self::Other(oid)
+        }
+    }
+}
+
+/// The basic constraints of a certificate, as per the basic constraints extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BasicConstraints {
+    /// Whether the certificate is a CA certificate
+    pub ca: bool,
+    /// The maximum number of intermediate CA certificates which may follow this certificate
+    /// in a chain, or `None` if unlimited
+    pub path_len: Option<u32>,
+}
+
+/// Get the contents of an MbedTLS ASN.1 buffer
+fn buf(buf: &mbedtls_asn1_buf) -> &[u8] {
+    if buf.p.is_null() {
+        &[]
+    } else {
+        unsafe { core::slice::from_raw_parts(buf.p, buf.len) }
+    }
+}
+
+/// Iterate over the buffers of an MbedTLS ASN.1 sequence, which is empty if its first buffer is
+fn sequence(seq: &mbedtls_asn1_sequence) -> impl Iterator<Item = &mbedtls_asn1_buf> {
+    core::iter::successors(Some(seq), |seq| unsafe { seq.next.as_ref() })
+        .map(|seq| &seq.buf)
+        .filter(|buf| !buf.p.is_null())
+}
+
+/// Compute the SHA-256 hash of the contents of an MbedTLS ASN.1 buffer
+fn sha256(data: &mbedtls_asn1_buf) -> [u8; 32] {
+    let data = buf(data);
+    let mut hash = [0; 32];
+
+    // Cannot fail, as SHA-256 (rather than SHA-224) is requested
+    unsafe {
+        mbedtls_sha256(data.as_ptr(), data.len(), hash.as_mut_ptr(), 0);
+    }
+
+    hash
 }
 
 /// A parsed private key
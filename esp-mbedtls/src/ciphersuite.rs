@@ -0,0 +1,502 @@
+use core::ffi::c_int;
+
+use crate::sys::*;
+
+/// Generates the `Ciphersuite` enum from the `MBEDTLS_TLS_*` / `MBEDTLS_TLS1_3_*` constants in the bindings
+macro_rules! ciphersuites {
+    ($($variant:ident = $id:ident => $name:literal,)*) => {
+        /// A TLS 1.2 or TLS 1.3 ciphersuite
+        ///
+        /// The discriminant of each variant is the IANA ID of the ciphersuite.
+        ///
+        /// Note that not all ciphersuites listed here are necessarily compiled into MbedTLS;
+        /// use [Ciphersuite::is_supported] to check that.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        #[repr(u16)]
+        pub enum Ciphersuite {
+            $(
+                #[doc = $name]
+                $variant = $id as u16,
+            )*
+        }
+
+        impl Ciphersuite {
+            /// Get the ciphersuite corresponding to the provided IANA ID
+            ///
+            /// # Returns
+            /// - The ciphersuite, or `None` if the ID is not known
+            pub const fn from_id(id: u16) -> Option<Self> {
+                #[allow(non_upper_case_globals)]
+                match id as u32 {
+                    $($id => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Get the IANA name of the ciphersuite (e.g. `TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256`)
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+        }
+    };
+}
+
+ciphersuites! {
+    RsaWithNullMd5 = MBEDTLS_TLS_RSA_WITH_NULL_MD5
+        => "TLS_RSA_WITH_NULL_MD5",
+    RsaWithNullSha = MBEDTLS_TLS_RSA_WITH_NULL_SHA
+        => "TLS_RSA_WITH_NULL_SHA",
+    PskWithNullSha = MBEDTLS_TLS_PSK_WITH_NULL_SHA
+        => "TLS_PSK_WITH_NULL_SHA",
+    DhePskWithNullSha = MBEDTLS_TLS_DHE_PSK_WITH_NULL_SHA
+        => "TLS_DHE_PSK_WITH_NULL_SHA",
+    RsaPskWithNullSha = MBEDTLS_TLS_RSA_PSK_WITH_NULL_SHA
+        => "TLS_RSA_PSK_WITH_NULL_SHA",
+    RsaWithAes128CbcSha = MBEDTLS_TLS_RSA_WITH_AES_128_CBC_SHA
+        => "TLS_RSA_WITH_AES_128_CBC_SHA",
+    DheRsaWithAes128CbcSha = MBEDTLS_TLS_DHE_RSA_WITH_AES_128_CBC_SHA
+        => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA",
+    RsaWithAes256CbcSha = MBEDTLS_TLS_RSA_WITH_AES_256_CBC_SHA
+        => "TLS_RSA_WITH_AES_256_CBC_SHA",
+    DheRsaWithAes256CbcSha = MBEDTLS_TLS_DHE_RSA_WITH_AES_256_CBC_SHA
+        => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA",
+    RsaWithNullSha256 = MBEDTLS_TLS_RSA_WITH_NULL_SHA256
+        => "TLS_RSA_WITH_NULL_SHA256",
+    RsaWithAes128CbcSha256 = MBEDTLS_TLS_RSA_WITH_AES_128_CBC_SHA256
+        => "TLS_RSA_WITH_AES_128_CBC_SHA256",
+    RsaWithAes256CbcSha256 = MBEDTLS_TLS_RSA_WITH_AES_256_CBC_SHA256
+        => "TLS_RSA_WITH_AES_256_CBC_SHA256",
+    RsaWithCamellia128CbcSha = MBEDTLS_TLS_RSA_WITH_CAMELLIA_128_CBC_SHA
+        => "TLS_RSA_WITH_CAMELLIA_128_CBC_SHA",
+    DheRsaWithCamellia128CbcSha = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA
+        => "TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA",
+    DheRsaWithAes128CbcSha256 = MBEDTLS_TLS_DHE_RSA_WITH_AES_128_CBC_SHA256
+        => "TLS_DHE_RSA_WITH_AES_128_CBC_SHA256",
+    DheRsaWithAes256CbcSha256 = MBEDTLS_TLS_DHE_RSA_WITH_AES_256_CBC_SHA256
+        => "TLS_DHE_RSA_WITH_AES_256_CBC_SHA256",
+    RsaWithCamellia256CbcSha = MBEDTLS_TLS_RSA_WITH_CAMELLIA_256_CBC_SHA
+        => "TLS_RSA_WITH_CAMELLIA_256_CBC_SHA",
+    DheRsaWithCamellia256CbcSha = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA
+        => "TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA",
+    PskWithAes128CbcSha = MBEDTLS_TLS_PSK_WITH_AES_128_CBC_SHA
+        => "TLS_PSK_WITH_AES_128_CBC_SHA",
+    PskWithAes256CbcSha = MBEDTLS_TLS_PSK_WITH_AES_256_CBC_SHA
+        => "TLS_PSK_WITH_AES_256_CBC_SHA",
+    DhePskWithAes128CbcSha = MBEDTLS_TLS_DHE_PSK_WITH_AES_128_CBC_SHA
+        => "TLS_DHE_PSK_WITH_AES_128_CBC_SHA",
+    DhePskWithAes256CbcSha = MBEDTLS_TLS_DHE_PSK_WITH_AES_256_CBC_SHA
+        => "TLS_DHE_PSK_WITH_AES_256_CBC_SHA",
+    RsaPskWithAes128CbcSha = MBEDTLS_TLS_RSA_PSK_WITH_AES_128_CBC_SHA
+        => "TLS_RSA_PSK_WITH_AES_128_CBC_SHA",
+    RsaPskWithAes256CbcSha = MBEDTLS_TLS_RSA_PSK_WITH_AES_256_CBC_SHA
+        => "TLS_RSA_PSK_WITH_AES_256_CBC_SHA",
+    RsaWithAes128GcmSha256 = MBEDTLS_TLS_RSA_WITH_AES_128_GCM_SHA256
+        => "TLS_RSA_WITH_AES_128_GCM_SHA256",
+    RsaWithAes256GcmSha384 = MBEDTLS_TLS_RSA_WITH_AES_256_GCM_SHA384
+        => "TLS_RSA_WITH_AES_256_GCM_SHA384",
+    DheRsaWithAes128GcmSha256 = MBEDTLS_TLS_DHE_RSA_WITH_AES_128_GCM_SHA256
+        => "TLS_DHE_RSA_WITH_AES_128_GCM_SHA256",
+    DheRsaWithAes256GcmSha384 = MBEDTLS_TLS_DHE_RSA_WITH_AES_256_GCM_SHA384
+        => "TLS_DHE_RSA_WITH_AES_256_GCM_SHA384",
+    PskWithAes128GcmSha256 = MBEDTLS_TLS_PSK_WITH_AES_128_GCM_SHA256
+        => "TLS_PSK_WITH_AES_128_GCM_SHA256",
+    PskWithAes256GcmSha384 = MBEDTLS_TLS_PSK_WITH_AES_256_GCM_SHA384
+        => "TLS_PSK_WITH_AES_256_GCM_SHA384",
+    DhePskWithAes128GcmSha256 = MBEDTLS_TLS_DHE_PSK_WITH_AES_128_GCM_SHA256
+        => "TLS_DHE_PSK_WITH_AES_128_GCM_SHA256",
+    DhePskWithAes256GcmSha384 = MBEDTLS_TLS_DHE_PSK_WITH_AES_256_GCM_SHA384
+        => "TLS_DHE_PSK_WITH_AES_256_GCM_SHA384",
+    RsaPskWithAes128GcmSha256 = MBEDTLS_TLS_RSA_PSK_WITH_AES_128_GCM_SHA256
+        => "TLS_RSA_PSK_WITH_AES_128_GCM_SHA256",
+    RsaPskWithAes256GcmSha384 = MBEDTLS_TLS_RSA_PSK_WITH_AES_256_GCM_SHA384
+        => "TLS_RSA_PSK_WITH_AES_256_GCM_SHA384",
+    PskWithAes128CbcSha256 = MBEDTLS_TLS_PSK_WITH_AES_128_CBC_SHA256
+        => "TLS_PSK_WITH_AES_128_CBC_SHA256",
+    PskWithAes256CbcSha384 = MBEDTLS_TLS_PSK_WITH_AES_256_CBC_SHA384
+        => "TLS_PSK_WITH_AES_256_CBC_SHA384",
+    PskWithNullSha256 = MBEDTLS_TLS_PSK_WITH_NULL_SHA256
+        => "TLS_PSK_WITH_NULL_SHA256",
+    PskWithNullSha384 = MBEDTLS_TLS_PSK_WITH_NULL_SHA384
+        => "TLS_PSK_WITH_NULL_SHA384",
+    DhePskWithAes128CbcSha256 = MBEDTLS_TLS_DHE_PSK_WITH_AES_128_CBC_SHA256
+        => "TLS_DHE_PSK_WITH_AES_128_CBC_SHA256",
+    DhePskWithAes256CbcSha384 = MBEDTLS_TLS_DHE_PSK_WITH_AES_256_CBC_SHA384
+        => "TLS_DHE_PSK_WITH_AES_256_CBC_SHA384",
+    DhePskWithNullSha256 = MBEDTLS_TLS_DHE_PSK_WITH_NULL_SHA256
+        => "TLS_DHE_PSK_WITH_NULL_SHA256",
+    DhePskWithNullSha384 = MBEDTLS_TLS_DHE_PSK_WITH_NULL_SHA384
+        => "TLS_DHE_PSK_WITH_NULL_SHA384",
+    RsaPskWithAes128CbcSha256 = MBEDTLS_TLS_RSA_PSK_WITH_AES_128_CBC_SHA256
+        => "TLS_RSA_PSK_WITH_AES_128_CBC_SHA256",
+    RsaPskWithAes256CbcSha384 = MBEDTLS_TLS_RSA_PSK_WITH_AES_256_CBC_SHA384
+        => "TLS_RSA_PSK_WITH_AES_256_CBC_SHA384",
+    RsaPskWithNullSha256 = MBEDTLS_TLS_RSA_PSK_WITH_NULL_SHA256
+        => "TLS_RSA_PSK_WITH_NULL_SHA256",
+    RsaPskWithNullSha384 = MBEDTLS_TLS_RSA_PSK_WITH_NULL_SHA384
+        => "TLS_RSA_PSK_WITH_NULL_SHA384",
+    RsaWithCamellia128CbcSha256 = MBEDTLS_TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_RSA_WITH_CAMELLIA_128_CBC_SHA256",
+    DheRsaWithCamellia128CbcSha256 = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_DHE_RSA_WITH_CAMELLIA_128_CBC_SHA256",
+    RsaWithCamellia256CbcSha256 = MBEDTLS_TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256
+        => "TLS_RSA_WITH_CAMELLIA_256_CBC_SHA256",
+    DheRsaWithCamellia256CbcSha256 = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256
+        => "TLS_DHE_RSA_WITH_CAMELLIA_256_CBC_SHA256",
+    EcdhEcdsaWithNullSha = MBEDTLS_TLS_ECDH_ECDSA_WITH_NULL_SHA
+        => "TLS_ECDH_ECDSA_WITH_NULL_SHA",
+    EcdhEcdsaWithAes128CbcSha = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA
+        => "TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA",
+    EcdhEcdsaWithAes256CbcSha = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA
+        => "TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA",
+    EcdheEcdsaWithNullSha = MBEDTLS_TLS_ECDHE_ECDSA_WITH_NULL_SHA
+        => "TLS_ECDHE_ECDSA_WITH_NULL_SHA",
+    EcdheEcdsaWithAes128CbcSha = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA
+        => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA",
+    EcdheEcdsaWithAes256CbcSha = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA
+        => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA",
+    EcdhRsaWithNullSha = MBEDTLS_TLS_ECDH_RSA_WITH_NULL_SHA
+        => "TLS_ECDH_RSA_WITH_NULL_SHA",
+    EcdhRsaWithAes128CbcSha = MBEDTLS_TLS_ECDH_RSA_WITH_AES_128_CBC_SHA
+        => "TLS_ECDH_RSA_WITH_AES_128_CBC_SHA",
+    EcdhRsaWithAes256CbcSha = MBEDTLS_TLS_ECDH_RSA_WITH_AES_256_CBC_SHA
+        => "TLS_ECDH_RSA_WITH_AES_256_CBC_SHA",
+    EcdheRsaWithNullSha = MBEDTLS_TLS_ECDHE_RSA_WITH_NULL_SHA
+        => "TLS_ECDHE_RSA_WITH_NULL_SHA",
+    EcdheRsaWithAes128CbcSha = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA
+        => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA",
+    EcdheRsaWithAes256CbcSha = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA
+        => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA",
+    EcdheEcdsaWithAes128CbcSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_AES_128_CBC_SHA256",
+    EcdheEcdsaWithAes256CbcSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_AES_256_CBC_SHA384",
+    EcdhEcdsaWithAes128CbcSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256
+        => "TLS_ECDH_ECDSA_WITH_AES_128_CBC_SHA256",
+    EcdhEcdsaWithAes256CbcSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384
+        => "TLS_ECDH_ECDSA_WITH_AES_256_CBC_SHA384",
+    EcdheRsaWithAes128CbcSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256
+        => "TLS_ECDHE_RSA_WITH_AES_128_CBC_SHA256",
+    EcdheRsaWithAes256CbcSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384
+        => "TLS_ECDHE_RSA_WITH_AES_256_CBC_SHA384",
+    EcdhRsaWithAes128CbcSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256
+        => "TLS_ECDH_RSA_WITH_AES_128_CBC_SHA256",
+    EcdhRsaWithAes256CbcSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384
+        => "TLS_ECDH_RSA_WITH_AES_256_CBC_SHA384",
+    EcdheEcdsaWithAes128GcmSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_AES_128_GCM_SHA256",
+    EcdheEcdsaWithAes256GcmSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_AES_256_GCM_SHA384",
+    EcdhEcdsaWithAes128GcmSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256
+        => "TLS_ECDH_ECDSA_WITH_AES_128_GCM_SHA256",
+    EcdhEcdsaWithAes256GcmSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384
+        => "TLS_ECDH_ECDSA_WITH_AES_256_GCM_SHA384",
+    EcdheRsaWithAes128GcmSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256
+        => "TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256",
+    EcdheRsaWithAes256GcmSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384
+        => "TLS_ECDHE_RSA_WITH_AES_256_GCM_SHA384",
+    EcdhRsaWithAes128GcmSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256
+        => "TLS_ECDH_RSA_WITH_AES_128_GCM_SHA256",
+    EcdhRsaWithAes256GcmSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384
+        => "TLS_ECDH_RSA_WITH_AES_256_GCM_SHA384",
+    EcdhePskWithAes128CbcSha = MBEDTLS_TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA
+        => "TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA",
+    EcdhePskWithAes256CbcSha = MBEDTLS_TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA
+        => "TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA",
+    EcdhePskWithAes128CbcSha256 = MBEDTLS_TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256
+        => "TLS_ECDHE_PSK_WITH_AES_128_CBC_SHA256",
+    EcdhePskWithAes256CbcSha384 = MBEDTLS_TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384
+        => "TLS_ECDHE_PSK_WITH_AES_256_CBC_SHA384",
+    EcdhePskWithNullSha = MBEDTLS_TLS_ECDHE_PSK_WITH_NULL_SHA
+        => "TLS_ECDHE_PSK_WITH_NULL_SHA",
+    EcdhePskWithNullSha256 = MBEDTLS_TLS_ECDHE_PSK_WITH_NULL_SHA256
+        => "TLS_ECDHE_PSK_WITH_NULL_SHA256",
+    EcdhePskWithNullSha384 = MBEDTLS_TLS_ECDHE_PSK_WITH_NULL_SHA384
+        => "TLS_ECDHE_PSK_WITH_NULL_SHA384",
+    RsaWithAria128CbcSha256 = MBEDTLS_TLS_RSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_RSA_WITH_ARIA_128_CBC_SHA256",
+    RsaWithAria256CbcSha384 = MBEDTLS_TLS_RSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_RSA_WITH_ARIA_256_CBC_SHA384",
+    DheRsaWithAria128CbcSha256 = MBEDTLS_TLS_DHE_RSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_DHE_RSA_WITH_ARIA_128_CBC_SHA256",
+    DheRsaWithAria256CbcSha384 = MBEDTLS_TLS_DHE_RSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_DHE_RSA_WITH_ARIA_256_CBC_SHA384",
+    EcdheEcdsaWithAria128CbcSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_ARIA_128_CBC_SHA256",
+    EcdheEcdsaWithAria256CbcSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_ARIA_256_CBC_SHA384",
+    EcdhEcdsaWithAria128CbcSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_ECDH_ECDSA_WITH_ARIA_128_CBC_SHA256",
+    EcdhEcdsaWithAria256CbcSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_ECDH_ECDSA_WITH_ARIA_256_CBC_SHA384",
+    EcdheRsaWithAria128CbcSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_ECDHE_RSA_WITH_ARIA_128_CBC_SHA256",
+    EcdheRsaWithAria256CbcSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_ECDHE_RSA_WITH_ARIA_256_CBC_SHA384",
+    EcdhRsaWithAria128CbcSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_ARIA_128_CBC_SHA256
+        => "TLS_ECDH_RSA_WITH_ARIA_128_CBC_SHA256",
+    EcdhRsaWithAria256CbcSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_ARIA_256_CBC_SHA384
+        => "TLS_ECDH_RSA_WITH_ARIA_256_CBC_SHA384",
+    RsaWithAria128GcmSha256 = MBEDTLS_TLS_RSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_RSA_WITH_ARIA_128_GCM_SHA256",
+    RsaWithAria256GcmSha384 = MBEDTLS_TLS_RSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_RSA_WITH_ARIA_256_GCM_SHA384",
+    DheRsaWithAria128GcmSha256 = MBEDTLS_TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_DHE_RSA_WITH_ARIA_128_GCM_SHA256",
+    DheRsaWithAria256GcmSha384 = MBEDTLS_TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_DHE_RSA_WITH_ARIA_256_GCM_SHA384",
+    EcdheEcdsaWithAria128GcmSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_ARIA_128_GCM_SHA256",
+    EcdheEcdsaWithAria256GcmSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_ARIA_256_GCM_SHA384",
+    EcdhEcdsaWithAria128GcmSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_ECDH_ECDSA_WITH_ARIA_128_GCM_SHA256",
+    EcdhEcdsaWithAria256GcmSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_ECDH_ECDSA_WITH_ARIA_256_GCM_SHA384",
+    EcdheRsaWithAria128GcmSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_ECDHE_RSA_WITH_ARIA_128_GCM_SHA256",
+    EcdheRsaWithAria256GcmSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_ECDHE_RSA_WITH_ARIA_256_GCM_SHA384",
+    EcdhRsaWithAria128GcmSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_ARIA_128_GCM_SHA256
+        => "TLS_ECDH_RSA_WITH_ARIA_128_GCM_SHA256",
+    EcdhRsaWithAria256GcmSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_ARIA_256_GCM_SHA384
+        => "TLS_ECDH_RSA_WITH_ARIA_256_GCM_SHA384",
+    PskWithAria128CbcSha256 = MBEDTLS_TLS_PSK_WITH_ARIA_128_CBC_SHA256
+        => "TLS_PSK_WITH_ARIA_128_CBC_SHA256",
+    PskWithAria256CbcSha384 = MBEDTLS_TLS_PSK_WITH_ARIA_256_CBC_SHA384
+        => "TLS_PSK_WITH_ARIA_256_CBC_SHA384",
+    DhePskWithAria128CbcSha256 = MBEDTLS_TLS_DHE_PSK_WITH_ARIA_128_CBC_SHA256
+        => "TLS_DHE_PSK_WITH_ARIA_128_CBC_SHA256",
+    DhePskWithAria256CbcSha384 = MBEDTLS_TLS_DHE_PSK_WITH_ARIA_256_CBC_SHA384
+        => "TLS_DHE_PSK_WITH_ARIA_256_CBC_SHA384",
+    RsaPskWithAria128CbcSha256 = MBEDTLS_TLS_RSA_PSK_WITH_ARIA_128_CBC_SHA256
+        => "TLS_RSA_PSK_WITH_ARIA_128_CBC_SHA256",
+    RsaPskWithAria256CbcSha384 = MBEDTLS_TLS_RSA_PSK_WITH_ARIA_256_CBC_SHA384
+        => "TLS_RSA_PSK_WITH_ARIA_256_CBC_SHA384",
+    PskWithAria128GcmSha256 = MBEDTLS_TLS_PSK_WITH_ARIA_128_GCM_SHA256
+        => "TLS_PSK_WITH_ARIA_128_GCM_SHA256",
+    PskWithAria256GcmSha384 = MBEDTLS_TLS_PSK_WITH_ARIA_256_GCM_SHA384
+        => "TLS_PSK_WITH_ARIA_256_GCM_SHA384",
+    DhePskWithAria128GcmSha256 = MBEDTLS_TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256
+        => "TLS_DHE_PSK_WITH_ARIA_128_GCM_SHA256",
+    DhePskWithAria256GcmSha384 = MBEDTLS_TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384
+        => "TLS_DHE_PSK_WITH_ARIA_256_GCM_SHA384",
+    RsaPskWithAria128GcmSha256 = MBEDTLS_TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256
+        => "TLS_RSA_PSK_WITH_ARIA_128_GCM_SHA256",
+    RsaPskWithAria256GcmSha384 = MBEDTLS_TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384
+        => "TLS_RSA_PSK_WITH_ARIA_256_GCM_SHA384",
+    EcdhePskWithAria128CbcSha256 = MBEDTLS_TLS_ECDHE_PSK_WITH_ARIA_128_CBC_SHA256
+        => "TLS_ECDHE_PSK_WITH_ARIA_128_CBC_SHA256",
+    EcdhePskWithAria256CbcSha384 = MBEDTLS_TLS_ECDHE_PSK_WITH_ARIA_256_CBC_SHA384
+        => "TLS_ECDHE_PSK_WITH_ARIA_256_CBC_SHA384",
+    EcdheEcdsaWithCamellia128CbcSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_CBC_SHA256",
+    EcdheEcdsaWithCamellia256CbcSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_CBC_SHA384",
+    EcdhEcdsaWithCamellia128CbcSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_ECDH_ECDSA_WITH_CAMELLIA_128_CBC_SHA256",
+    EcdhEcdsaWithCamellia256CbcSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_ECDH_ECDSA_WITH_CAMELLIA_256_CBC_SHA384",
+    EcdheRsaWithCamellia128CbcSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_ECDHE_RSA_WITH_CAMELLIA_128_CBC_SHA256",
+    EcdheRsaWithCamellia256CbcSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_ECDHE_RSA_WITH_CAMELLIA_256_CBC_SHA384",
+    EcdhRsaWithCamellia128CbcSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_ECDH_RSA_WITH_CAMELLIA_128_CBC_SHA256",
+    EcdhRsaWithCamellia256CbcSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_ECDH_RSA_WITH_CAMELLIA_256_CBC_SHA384",
+    RsaWithCamellia128GcmSha256 = MBEDTLS_TLS_RSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_RSA_WITH_CAMELLIA_128_GCM_SHA256",
+    RsaWithCamellia256GcmSha384 = MBEDTLS_TLS_RSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_RSA_WITH_CAMELLIA_256_GCM_SHA384",
+    DheRsaWithCamellia128GcmSha256 = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_DHE_RSA_WITH_CAMELLIA_128_GCM_SHA256",
+    DheRsaWithCamellia256GcmSha384 = MBEDTLS_TLS_DHE_RSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_DHE_RSA_WITH_CAMELLIA_256_GCM_SHA384",
+    EcdheEcdsaWithCamellia128GcmSha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_CAMELLIA_128_GCM_SHA256",
+    EcdheEcdsaWithCamellia256GcmSha384 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_ECDHE_ECDSA_WITH_CAMELLIA_256_GCM_SHA384",
+    EcdhEcdsaWithCamellia128GcmSha256 = MBEDTLS_TLS_ECDH_ECDSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_ECDH_ECDSA_WITH_CAMELLIA_128_GCM_SHA256",
+    EcdhEcdsaWithCamellia256GcmSha384 = MBEDTLS_TLS_ECDH_ECDSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_ECDH_ECDSA_WITH_CAMELLIA_256_GCM_SHA384",
+    EcdheRsaWithCamellia128GcmSha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_ECDHE_RSA_WITH_CAMELLIA_128_GCM_SHA256",
+    EcdheRsaWithCamellia256GcmSha384 = MBEDTLS_TLS_ECDHE_RSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_ECDHE_RSA_WITH_CAMELLIA_256_GCM_SHA384",
+    EcdhRsaWithCamellia128GcmSha256 = MBEDTLS_TLS_ECDH_RSA_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_ECDH_RSA_WITH_CAMELLIA_128_GCM_SHA256",
+    EcdhRsaWithCamellia256GcmSha384 = MBEDTLS_TLS_ECDH_RSA_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_ECDH_RSA_WITH_CAMELLIA_256_GCM_SHA384",
+    PskWithCamellia128GcmSha256 = MBEDTLS_TLS_PSK_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_PSK_WITH_CAMELLIA_128_GCM_SHA256",
+    PskWithCamellia256GcmSha384 = MBEDTLS_TLS_PSK_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_PSK_WITH_CAMELLIA_256_GCM_SHA384",
+    DhePskWithCamellia128GcmSha256 = MBEDTLS_TLS_DHE_PSK_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_DHE_PSK_WITH_CAMELLIA_128_GCM_SHA256",
+    DhePskWithCamellia256GcmSha384 = MBEDTLS_TLS_DHE_PSK_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_DHE_PSK_WITH_CAMELLIA_256_GCM_SHA384",
+    RsaPskWithCamellia128GcmSha256 = MBEDTLS_TLS_RSA_PSK_WITH_CAMELLIA_128_GCM_SHA256
+        => "TLS_RSA_PSK_WITH_CAMELLIA_128_GCM_SHA256",
+    RsaPskWithCamellia256GcmSha384 = MBEDTLS_TLS_RSA_PSK_WITH_CAMELLIA_256_GCM_SHA384
+        => "TLS_RSA_PSK_WITH_CAMELLIA_256_GCM_SHA384",
+    PskWithCamellia128CbcSha256 = MBEDTLS_TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_PSK_WITH_CAMELLIA_128_CBC_SHA256",
+    PskWithCamellia256CbcSha384 = MBEDTLS_TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_PSK_WITH_CAMELLIA_256_CBC_SHA384",
+    DhePskWithCamellia128CbcSha256 = MBEDTLS_TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_DHE_PSK_WITH_CAMELLIA_128_CBC_SHA256",
+    DhePskWithCamellia256CbcSha384 = MBEDTLS_TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_DHE_PSK_WITH_CAMELLIA_256_CBC_SHA384",
+    RsaPskWithCamellia128CbcSha256 = MBEDTLS_TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_RSA_PSK_WITH_CAMELLIA_128_CBC_SHA256",
+    RsaPskWithCamellia256CbcSha384 = MBEDTLS_TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_RSA_PSK_WITH_CAMELLIA_256_CBC_SHA384",
+    EcdhePskWithCamellia128CbcSha256 = MBEDTLS_TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256
+        => "TLS_ECDHE_PSK_WITH_CAMELLIA_128_CBC_SHA256",
+    EcdhePskWithCamellia256CbcSha384 = MBEDTLS_TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384
+        => "TLS_ECDHE_PSK_WITH_CAMELLIA_256_CBC_SHA384",
+    RsaWithAes128Ccm = MBEDTLS_TLS_RSA_WITH_AES_128_CCM
+        => "TLS_RSA_WITH_AES_128_CCM",
+    RsaWithAes256Ccm = MBEDTLS_TLS_RSA_WITH_AES_256_CCM
+        => "TLS_RSA_WITH_AES_256_CCM",
+    DheRsaWithAes128Ccm = MBEDTLS_TLS_DHE_RSA_WITH_AES_128_CCM
+        => "TLS_DHE_RSA_WITH_AES_128_CCM",
+    DheRsaWithAes256Ccm = MBEDTLS_TLS_DHE_RSA_WITH_AES_256_CCM
+        => "TLS_DHE_RSA_WITH_AES_256_CCM",
+    RsaWithAes128Ccm8 = MBEDTLS_TLS_RSA_WITH_AES_128_CCM_8
+        => "TLS_RSA_WITH_AES_128_CCM_8",
+    RsaWithAes256Ccm8 = MBEDTLS_TLS_RSA_WITH_AES_256_CCM_8
+        => "TLS_RSA_WITH_AES_256_CCM_8",
+    DheRsaWithAes128Ccm8 = MBEDTLS_TLS_DHE_RSA_WITH_AES_128_CCM_8
+        => "TLS_DHE_RSA_WITH_AES_128_CCM_8",
+    DheRsaWithAes256Ccm8 = MBEDTLS_TLS_DHE_RSA_WITH_AES_256_CCM_8
+        => "TLS_DHE_RSA_WITH_AES_256_CCM_8",
+    PskWithAes128Ccm = MBEDTLS_TLS_PSK_WITH_AES_128_CCM
+        => "TLS_PSK_WITH_AES_128_CCM",
+    PskWithAes256Ccm = MBEDTLS_TLS_PSK_WITH_AES_256_CCM
+        => "TLS_PSK_WITH_AES_256_CCM",
+    DhePskWithAes128Ccm = MBEDTLS_TLS_DHE_PSK_WITH_AES_128_CCM
+        => "TLS_DHE_PSK_WITH_AES_128_CCM",
+    DhePskWithAes256Ccm = MBEDTLS_TLS_DHE_PSK_WITH_AES_256_CCM
+        => "TLS_DHE_PSK_WITH_AES_256_CCM",
+    PskWithAes128Ccm8 = MBEDTLS_TLS_PSK_WITH_AES_128_CCM_8
+        => "TLS_PSK_WITH_AES_128_CCM_8",
+    PskWithAes256Ccm8 = MBEDTLS_TLS_PSK_WITH_AES_256_CCM_8
+        => "TLS_PSK_WITH_AES_256_CCM_8",
+    DhePskWithAes128Ccm8 = MBEDTLS_TLS_DHE_PSK_WITH_AES_128_CCM_8
+        => "TLS_DHE_PSK_WITH_AES_128_CCM_8",
+    DhePskWithAes256Ccm8 = MBEDTLS_TLS_DHE_PSK_WITH_AES_256_CCM_8
+        => "TLS_DHE_PSK_WITH_AES_256_CCM_8",
+    EcdheEcdsaWithAes128Ccm = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_128_CCM
+        => "TLS_ECDHE_ECDSA_WITH_AES_128_CCM",
+    EcdheEcdsaWithAes256Ccm = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_256_CCM
+        => "TLS_ECDHE_ECDSA_WITH_AES_256_CCM",
+    EcdheEcdsaWithAes128Ccm8 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8
+        => "TLS_ECDHE_ECDSA_WITH_AES_128_CCM_8",
+    EcdheEcdsaWithAes256Ccm8 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8
+        => "TLS_ECDHE_ECDSA_WITH_AES_256_CCM_8",
+    EcjpakeWithAes128Ccm8 = MBEDTLS_TLS_ECJPAKE_WITH_AES_128_CCM_8
+        => "TLS_ECJPAKE_WITH_AES_128_CCM_8",
+    EcdheRsaWithChacha20Poly1305Sha256 = MBEDTLS_TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_ECDHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+    EcdheEcdsaWithChacha20Poly1305Sha256 = MBEDTLS_TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_ECDHE_ECDSA_WITH_CHACHA20_POLY1305_SHA256",
+    DheRsaWithChacha20Poly1305Sha256 = MBEDTLS_TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_DHE_RSA_WITH_CHACHA20_POLY1305_SHA256",
+    PskWithChacha20Poly1305Sha256 = MBEDTLS_TLS_PSK_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_PSK_WITH_CHACHA20_POLY1305_SHA256",
+    EcdhePskWithChacha20Poly1305Sha256 = MBEDTLS_TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_ECDHE_PSK_WITH_CHACHA20_POLY1305_SHA256",
+    DhePskWithChacha20Poly1305Sha256 = MBEDTLS_TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_DHE_PSK_WITH_CHACHA20_POLY1305_SHA256",
+    RsaPskWithChacha20Poly1305Sha256 = MBEDTLS_TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256
+        => "TLS_RSA_PSK_WITH_CHACHA20_POLY1305_SHA256",
+    Tls13Aes128GcmSha256 = MBEDTLS_TLS1_3_AES_128_GCM_SHA256
+        => "TLS_AES_128_GCM_SHA256",
+    Tls13Aes256GcmSha384 = MBEDTLS_TLS1_3_AES_256_GCM_SHA384
+        => "TLS_AES_256_GCM_SHA384",
+    Tls13Chacha20Poly1305Sha256 = MBEDTLS_TLS1_3_CHACHA20_POLY1305_SHA256
+        => "TLS_CHACHA20_POLY1305_SHA256",
+    Tls13Aes128CcmSha256 = MBEDTLS_TLS1_3_AES_128_CCM_SHA256
+        => "TLS_AES_128_CCM_SHA256",
+    Tls13Aes128Ccm8Sha256 = MBEDTLS_TLS1_3_AES_128_CCM_8_SHA256
+        => "TLS_AES_128_CCM_8_SHA256",
+}
+
+impl Ciphersuite {
+    /// Get the IANA ID of the ciphersuite
+    pub const fn id(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Return `true` if the ciphersuite is compiled into MbedTLS and can therefore be negotiated
+    pub fn is_supported(&self) -> bool {
+        !unsafe { mbedtls_ssl_ciphersuite_from_id(self.id() as c_int) }.is_null()
+    }
+}
+
+impl core::fmt::Display for Ciphersuite {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ciphersuite;
+
+    /// The pre-generated bindings, which define the IDs of all the ciphersuites known to MbedTLS,
+    /// regardless of the build configuration
+    const BINDINGS: &str =
+        include_str!("../../esp-mbedtls-sys/src/include/riscv32imc-unknown-none-elf.rs");
+
+    /// Check that each ciphersuite constant in the bindings has a `Ciphersuite` variant with the same ID and name
+    #[test]
+    fn all_ciphersuites() {
+        let mut count = 0;
+
+        for line in BINDINGS.lines() {
+            let Some((name, value)) = line
+                .trim()
+                .strip_prefix("pub const MBEDTLS_")
+                .and_then(|line| line.strip_suffix(';'))
+                .and_then(|line| line.split_once(": u32 = "))
+            else {
+                continue;
+            };
+
+            // The TLS 1.2 ciphersuites are named `MBEDTLS_TLS_<kx>_WITH_<cipher>_<mac>`, while the
+            // TLS 1.3 ones are named `MBEDTLS_TLS1_3_<cipher>_<hash>` rather than `MBEDTLS_TLS_<cipher>_<hash>`
+            let name = if let Some(suffix) = name
+                .strip_prefix("TLS_")
+                .filter(|suffix| suffix.contains("_WITH_"))
+            {
+                suffix
+            } else if let Some(suffix) = name
+                .strip_prefix("TLS1_3_")
+                .filter(|suffix| suffix.starts_with("AES_") || suffix.starts_with("CHACHA20_"))
+            {
+                suffix
+            } else {
+                continue;
+            };
+
+            let id = value.parse::<u16>().unwrap();
+
+            let ciphersuite = Ciphersuite::from_id(id);
+            assert!(ciphersuite.is_some(), "No variant for {name} ({id:#06x})");
+
+            let ciphersuite = ciphersuite.unwrap();
+            assert_eq!(ciphersuite.id(), id);
+            assert_eq!(ciphersuite.name().strip_prefix("TLS_"), Some(name));
+
+            count += 1;
+        }
+
+        assert!(count > 0, "No ciphersuite constants found in the bindings");
+    }
+}
@@ -0,0 +1,92 @@
+use core::ffi::c_void;
+
+use super::sys::*;
+use super::{mbedtls_rng, MInit, MRc, TlsReference};
+
+/// A server-side DTLS cookie manager, generating and verifying the cookies of the
+/// HelloVerifyRequest exchange (RFC 6347, section 4.2.1).
+///
+/// With cookies, a DTLS server only starts a handshake with a client once the client has proven
+/// that it can receive datagrams at its (claimed) address. This prevents the server from being used
+/// for amplification attacks with spoofed source addresses, as well as from allocating state for such
+/// clients.
+///
+/// Instances are cheap to clone and all clones share the same key, so that a single instance
+/// can be plugged into the `ServerSessionConfig` used by multiple DTLS sessions.
+///
+/// Cookies are HMAC-SHA-256 tags over the client address, keyed with a randomly generated key.
+#[derive(Clone)]
+pub struct DtlsCookies<'d> {
+    ctx: MRc<mbedtls_ssl_cookie_ctx>,
+    _tls: TlsReference<'d>,
+}
+
+impl<'d> DtlsCookies<'d> {
+    /// Create a new cookie manager with a randomly generated key
+    ///
+    /// # Arguments
+    /// - `tls` - A reference to the active `Tls` instance, which provides the RNG for the key generation
+    pub fn new(tls: TlsReference<'d>) -> Result<Self, MbedtlsError> {
+        let ctx = MRc::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        merr!(unsafe {
+            mbedtls_ssl_cookie_setup(
+                &*ctx as *const _ as *mut _,
+                Some(mbedtls_rng),
+                core::ptr::null_mut(),
+            )
+        })?;
+
+        Ok(Self { ctx, _tls: tls })
+    }
+
+    /// Set the validity period of the issued cookies
+    ///
+    /// # Arguments
+    /// - `timeout` - The validity period, in seconds (60 by default). Note that cookies can only
+    ///   expire if MbedTLS has access to the current time.
+    pub fn set_timeout(&self, timeout: u32) {
+        unsafe {
+            mbedtls_ssl_cookie_set_timeout(&*self.ctx as *const _ as *mut _, timeout as _);
+        }
+    }
+
+    /// Get the validity period of the issued cookies, in seconds
+    pub fn timeout(&self) -> u32 {
+        self.ctx.private_timeout as _
+    }
+
+    /// Get a raw pointer to the cookie context, to be used as the MbedTLS cookie callbacks' context
+    pub(crate) fn as_ptr(&self) -> *mut c_void {
+        &*self.ctx as *const _ as *mut c_void
+    }
+}
+
+impl core::fmt::Debug for DtlsCookies<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DtlsCookies")
+            .field("timeout", &self.timeout())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DtlsCookies<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "DtlsCookies {{ timeout: {}, .. }}", self.timeout())
+    }
+}
+
+impl MInit for mbedtls_ssl_cookie_ctx {
+    fn init(&mut self) {
+        unsafe {
+            mbedtls_ssl_cookie_init(self);
+        }
+    }
+
+    fn deinit(&mut self) {
+        unsafe {
+            mbedtls_ssl_cookie_free(self);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 #![no_std]
 #![allow(clippy::uninlined_format_args)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::cell::RefCell;
 use core::ffi::{c_char, c_int, c_uchar, c_void, CStr};
 use core::marker::PhantomData;
@@ -16,22 +19,41 @@ use crate::sys::{
     mbedtls_ctr_drbg_context, mbedtls_ctr_drbg_free, mbedtls_ctr_drbg_init, mbedtls_pk_context,
     mbedtls_pk_free, mbedtls_pk_init, mbedtls_ssl_conf_dbg, mbedtls_ssl_config,
     mbedtls_ssl_config_free, mbedtls_ssl_config_init, mbedtls_ssl_context, mbedtls_ssl_free,
-    mbedtls_ssl_init, mbedtls_x509_crt, mbedtls_x509_crt_free, mbedtls_x509_crt_init,
+    mbedtls_ssl_init, mbedtls_ssl_session, mbedtls_ssl_session_free, mbedtls_ssl_session_init,
+    mbedtls_x509_crl, mbedtls_x509_crl_free, mbedtls_x509_crl_init, mbedtls_x509_crt,
+    mbedtls_x509_crt_free, mbedtls_x509_crt_init, MbedtlsError, MBEDTLS_ERR_SSL_ALLOC_FAILED,
+    MBEDTLS_ERR_SSL_BAD_INPUT_DATA,
 };
 
 use rand_core::CryptoRng;
 
+pub use alert::*;
+pub use algorithm::*;
+pub use cache::*;
 pub use cert::*;
+pub use ciphersuite::*;
+pub use cookie::*;
 #[cfg(feature = "edge-nal")]
 pub use edge_nal::*;
 pub use session::*;
+pub use ticket::*;
+#[cfg(feature = "std")]
+pub use time::StdTime;
+pub use time::TimeProvider;
 
 pub(crate) mod fmt; // MUST be the first so that the other modules can see it
 
+mod alert;
+mod algorithm;
+mod cache;
 mod cert;
+mod ciphersuite;
+mod cookie;
 #[cfg(feature = "edge-nal")]
 mod edge_nal;
 mod session;
+mod ticket;
+mod time;
 
 /// Re-export of the esp-mbedtls-sys crate so that users do not have to
 /// explicitly depend on it if they want to use the raw MbedTLS bindings.
@@ -74,6 +96,9 @@ impl<'d> Tls<'d> {
                 >(rng)
             });
 
+            #[cfg(all(not(target_os = "espidf"), mbedtls_platform_time_alt))]
+            time::hook_time();
+
             Ok(Self(PhantomData))
         })
     }
@@ -81,6 +106,29 @@ impl<'d> Tls<'d> {
     pub(crate) fn release(&mut self) {
         critical_section::with(|cs| {
             *RNG.borrow(cs).borrow_mut() = None;
+            *time::TIME.borrow(cs).borrow_mut() = None;
+        });
+    }
+
+    /// Set the wall-clock time source used for checking the validity period of certificates and CRLs,
+    /// and for expiring session tickets and cached sessions
+    ///
+    /// Without a time provider (the default), the validity periods are not checked, and TLS 1.3 servers
+    /// only resume the sessions of tickets used within a few seconds (see [TimeProvider]).
+    ///
+    /// Note that on ESP-IDF, MbedTLS always uses the system time, and the time provider is ignored.
+    /// The time provider is ignored as well if MbedTLS is built without `MBEDTLS_PLATFORM_TIME_ALT`.
+    ///
+    /// # Arguments
+    /// - `time` - The time provider, or `None` to remove the current one
+    pub fn set_time_provider(&mut self, time: Option<&'d (dyn TimeProvider + Sync)>) {
+        critical_section::with(|cs| {
+            *time::TIME.borrow(cs).borrow_mut() = time.map(|time| unsafe {
+                core::mem::transmute::<
+                    &'d (dyn TimeProvider + Sync),
+                    &'static (dyn TimeProvider + Sync),
+                >(time)
+            });
         });
     }
 
@@ -156,8 +204,8 @@ impl<'d> Drop for Tls<'d> {
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TlsReference<'a>(PhantomData<&'a ()>);
 
-/// The minimum TLS version that will be supported by a particular `Session` instance
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A TLS protocol version, as supported by a particular `Session` instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum TlsVersion {
     /// TLS 1.2
@@ -173,6 +221,14 @@ impl TlsVersion {
             TlsVersion::Tls1_3 => 0x304,
         }
     }
+
+    fn from_mbed_tls_version(version: u32) -> Option<Self> {
+        match version {
+            0x303 => Some(TlsVersion::Tls1_2),
+            0x304 => Some(TlsVersion::Tls1_3),
+            _ => None,
+        }
+    }
 }
 
 /// An internal trait to be implemented on MbedTLS structures.
@@ -229,6 +285,20 @@ impl MInit for mbedtls_ssl_config {
     }
 }
 
+impl MInit for mbedtls_ssl_session {
+    fn init(&mut self) {
+        unsafe {
+            mbedtls_ssl_session_init(self);
+        }
+    }
+
+    fn deinit(&mut self) {
+        unsafe {
+            mbedtls_ssl_session_free(self);
+        }
+    }
+}
+
 impl MInit for mbedtls_x509_crt {
     fn init(&mut self) {
         unsafe {
@@ -243,6 +313,20 @@ impl MInit for mbedtls_x509_crt {
     }
 }
 
+impl MInit for mbedtls_x509_crl {
+    fn init(&mut self) {
+        unsafe {
+            mbedtls_x509_crl_init(self);
+        }
+    }
+
+    fn deinit(&mut self) {
+        unsafe {
+            mbedtls_x509_crl_free(self);
+        }
+    }
+}
+
 impl MInit for mbedtls_pk_context {
     fn init(&mut self) {
         unsafe {
@@ -282,6 +366,25 @@ where
         })
     }
 
+    /// Create a new MBox holding the provided value
+    ///
+    /// Unlike `new`, the value is moved as-is into the allocation, without calling `MInit::init`,
+    /// so this is meant for Rust types which are not valid when zeroed.
+    ///
+    /// # Arguments
+    /// - `value` - The value to move into the MBox
+    ///
+    /// # Returns
+    /// - Some(MBox<T>) if the allocation was successful
+    /// - None if the allocation failed
+    fn new_with(value: T) -> Option<Self> {
+        NonNull::new(unsafe { mbedtls_calloc(1, size_of::<T>()) }.cast::<T>()).map(|ptr| {
+            unsafe { ptr.as_ptr().write(value) };
+
+            Self(ptr)
+        })
+    }
+
     /// Get a reference to the inner value
     fn as_ref(&self) -> &T {
         unsafe { self.0.as_ref() }
@@ -398,6 +501,96 @@ where
     }
 }
 
+/// A fixed-capacity, zero-terminated list of values (protocol names, IDs etc.) handed over to MbedTLS by pointer.
+///
+/// The last element is always left zeroed (`mbedtls_calloc` zeroes the memory) and acts as the list terminator,
+/// so the list can hold at most `N - 1` values.
+///
+/// Meant to be allocated with `MBox` so that its address is stable for as long as MbedTLS keeps a pointer to it.
+struct MList<T, const N: usize>([T; N]);
+
+impl<T, const N: usize> MInit for MList<T, N> {}
+
+impl<T, const N: usize> MList<T, N>
+where
+    T: Copy,
+{
+    /// Allocate a new list and fill it with the provided values
+    ///
+    /// # Returns
+    /// - Ok(MBox<MList<T, N>>) if the allocation was successful and the values did fit
+    /// - Err(MbedtlsError) with `MBEDTLS_ERR_SSL_ALLOC_FAILED` if the allocation failed
+    ///   or with `MBEDTLS_ERR_SSL_BAD_INPUT_DATA` if there are more than `N - 1` values
+    fn new<I>(values: I) -> Result<MBox<Self>, MbedtlsError>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut list =
+            MBox::<Self>::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        for (index, value) in values.into_iter().enumerate() {
+            if index >= N - 1 {
+                return Err(MbedtlsError::new(MBEDTLS_ERR_SSL_BAD_INPUT_DATA));
+            }
+
+            list.as_mut().0[index] = value;
+        }
+
+        Ok(list)
+    }
+
+    /// Get a raw pointer to the first element of the list
+    fn as_ptr(&self) -> *const T {
+        self.0.as_ptr()
+    }
+}
+
+/// A reference to a (possibly unsized) callback object (i.e. a `dyn Trait`) handed over to MbedTLS by pointer.
+///
+/// Necessary, because references to trait objects are fat pointers, while MbedTLS callbacks take a thin `void *` context.
+///
+/// Meant to be allocated with `MBox` so that its address is stable for as long as MbedTLS keeps a pointer to it.
+struct MCallback<'a, T>(Option<&'a T>)
+where
+    T: ?Sized;
+
+impl<T> MInit for MCallback<'_, T> where T: ?Sized {}
+
+impl<'a, T> MCallback<'a, T>
+where
+    T: ?Sized,
+{
+    /// Allocate a new callback reference
+    ///
+    /// # Returns
+    /// - Ok(MBox<MCallback<T>>) if the allocation was successful
+    /// - Err(MbedtlsError) with `MBEDTLS_ERR_SSL_ALLOC_FAILED` if the allocation failed
+    fn new(callback: &'a T) -> Result<MBox<Self>, MbedtlsError> {
+        let mut this =
+            MBox::<Self>::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        this.as_mut().0 = Some(callback);
+
+        Ok(this)
+    }
+
+    /// Get a raw pointer to be used as the MbedTLS callback context
+    fn as_ptr(&self) -> *mut c_void {
+        self as *const _ as *mut c_void
+    }
+
+    /// Get the callback object from a raw MbedTLS callback context
+    ///
+    /// # Safety
+    /// `ptr` must have been obtained with `MCallback::as_ptr` and the `MCallback` instance must still be alive
+    unsafe fn from_ptr<'b>(ptr: *mut c_void) -> &'b T
+    where
+        'a: 'b,
+    {
+        (ptr as *const Self).as_ref().unwrap().0.unwrap()
+    }
+}
+
 pub(crate) unsafe extern "C" fn mbedtls_rng(
     _param: *mut c_void,
     buf: *mut c_uchar,
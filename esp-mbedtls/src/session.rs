@@ -1,9 +1,18 @@
-use core::ffi::{c_int, c_void, CStr};
+use core::ffi::{c_char, c_int, c_uchar, c_void, CStr};
+use core::net::{IpAddr, SocketAddr};
+use core::time::Duration;
 
 use embedded_io::{Error, ErrorKind};
 
+use enumset::EnumSet;
+
 use super::sys::*;
-use super::{mbedtls_rng, Certificate, MBox, PrivateKey, Tls, TlsReference, TlsVersion};
+use super::{
+    mbedtls_rng, received_alert, session_cache_get, session_cache_set, AlertDescription,
+    AlertLevel, Certificate, CertificatePin, CertificateRef, Ciphersuite, Crl, DtlsCookies, MBox,
+    MCallback, MInit, MList, NamedGroup, PrivateKey, SessionCache, SessionTickets,
+    SignatureAlgorithm, Tls, TlsReference, TlsVersion, VerifyFlag,
+};
 
 pub use asynch::*;
 
@@ -21,7 +30,7 @@ pub enum AuthMode {
     Optional,
     /// Peer *must* present a valid certificate, handshake is aborted if verification failed. (default on client)
     Required,
-    /// Used only for sni_authmode
+    /// Used only for [SniResolution::auth_mode], meaning that the auth mode of the server configuration is used
     Unset,
 }
 
@@ -36,6 +45,51 @@ impl AuthMode {
     }
 }
 
+/// The maximum length of the plaintext fragments carried by the records of a session (RFC 6066)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MaxFragmentLength {
+    /// 512 bytes
+    Bytes512,
+    /// 1024 bytes
+    Bytes1024,
+    /// 2048 bytes
+    Bytes2048,
+    /// 4096 bytes
+    Bytes4096,
+}
+
+impl MaxFragmentLength {
+    /// Get the maximum fragment length, in bytes
+    pub const fn bytes(&self) -> usize {
+        match self {
+            MaxFragmentLength::Bytes512 => 512,
+            MaxFragmentLength::Bytes1024 => 1024,
+            MaxFragmentLength::Bytes2048 => 2048,
+            MaxFragmentLength::Bytes4096 => 4096,
+        }
+    }
+
+    fn mbedtls_code(&self) -> u8 {
+        (match self {
+            MaxFragmentLength::Bytes512 => MBEDTLS_SSL_MAX_FRAG_LEN_512,
+            MaxFragmentLength::Bytes1024 => MBEDTLS_SSL_MAX_FRAG_LEN_1024,
+            MaxFragmentLength::Bytes2048 => MBEDTLS_SSL_MAX_FRAG_LEN_2048,
+            MaxFragmentLength::Bytes4096 => MBEDTLS_SSL_MAX_FRAG_LEN_4096,
+        }) as u8
+    }
+
+    fn from_mbedtls_code(code: u8) -> Option<Self> {
+        match code as u32 {
+            MBEDTLS_SSL_MAX_FRAG_LEN_512 => Some(MaxFragmentLength::Bytes512),
+            MBEDTLS_SSL_MAX_FRAG_LEN_1024 => Some(MaxFragmentLength::Bytes1024),
+            MBEDTLS_SSL_MAX_FRAG_LEN_2048 => Some(MaxFragmentLength::Bytes2048),
+            MBEDTLS_SSL_MAX_FRAG_LEN_4096 => Some(MaxFragmentLength::Bytes4096),
+            _ => None,
+        }
+    }
+}
+
 /// The credentials (certificate and private key)
 /// used for client or server authentication
 #[derive(Debug, Clone)]
@@ -47,6 +101,139 @@ pub struct Credentials<'a> {
     pub private_key: PrivateKey,
 }
 
+/// A pre-shared key (PSK) along with its identity, used for PSK authentication
+/// as an alternative (or in addition) to certificate-based authentication
+#[derive(Clone)]
+pub struct Psk<'a> {
+    /// The PSK identity, which is sent to the server in the clear
+    pub identity: &'a [u8],
+    /// The pre-shared key itself
+    pub key: &'a [u8],
+}
+
+impl core::fmt::Debug for Psk<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        // Do not leak the key in the logs
+        f.debug_struct("Psk")
+            .field("identity", &self.identity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Psk<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        // Do not leak the key in the logs
+        defmt::write!(f, "Psk {{ identity: {=[u8]}, .. }}", self.identity)
+    }
+}
+
+/// A server-side hook resolving the pre-shared key (PSK) for the PSK identity presented by a client
+///
+/// Called during the handshake, for both TLS 1.2 PSK ciphersuites and TLS 1.3 PSK key exchange modes.
+pub trait PskLookup {
+    /// Look up the pre-shared key for the provided PSK identity
+    ///
+    /// # Arguments
+    /// - `identity` - The PSK identity presented by the client
+    ///
+    /// # Returns
+    /// - The pre-shared key, or `None` if the identity is unknown, in which case the handshake is aborted
+    fn lookup(&self, identity: &[u8]) -> Option<&[u8]>;
+}
+
+impl core::fmt::Debug for dyn PskLookup + '_ {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "PskLookup")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for dyn PskLookup + '_ {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "PskLookup")
+    }
+}
+
+/// The outcome of a successful [SniResolver::resolve] call
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SniResolution<'r> {
+    /// The server credentials to be used for the requested server name
+    pub creds: &'r Credentials<'r>,
+    /// The CA chain to be used for verifying the client certificate, if any
+    ///
+    /// If `None`, the CA chain (and CRL) of the server configuration is used.
+    pub ca_chain: Option<&'r Certificate<'r>>,
+    /// The CRL to be used along with `ca_chain`, if any
+    pub crl: Option<&'r Crl<'r>>,
+    /// The client certificate verification mode
+    ///
+    /// If [AuthMode::Unset], the auth mode of the server configuration is used.
+    pub auth_mode: AuthMode,
+}
+
+/// A server-side hook selecting the credentials to be used for the server name (SNI)
+/// requested by a client, e.g. for serving multiple host names from a single port
+///
+/// Called during the handshake, only if the client did send a server name.
+pub trait SniResolver {
+    /// Resolve the credentials for the provided server name
+    ///
+    /// # Arguments
+    /// - `server_name` - The server name requested by the client
+    ///
+    /// # Returns
+    /// - The credentials for the server name, or `None` if the server name is unknown,
+    ///   in which case the handshake is aborted
+    fn resolve(&self, server_name: &str) -> Option<SniResolution<'_>>;
+}
+
+impl core::fmt::Debug for dyn SniResolver + '_ {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "SniResolver")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for dyn SniResolver + '_ {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "SniResolver")
+    }
+}
+
+/// A hook applying custom policy to the verification of the peer certificate chain
+///
+/// Called during the handshake for each certificate in the chain, starting with the
+/// root (highest depth) and ending with the peer's own certificate (depth 0), after MbedTLS
+/// has performed its own verification of the certificate.
+pub trait CertVerifier {
+    /// Verify a certificate of the peer certificate chain
+    ///
+    /// # Arguments
+    /// - `cert` - The certificate being verified
+    /// - `depth` - The depth of the certificate in the chain, 0 being the peer's own certificate
+    /// - `flags` - The verification failure flags set by MbedTLS so far for this certificate
+    ///   (as returned by `tls_verification_details`). The flags can be removed (e.g. to tolerate
+    ///   an expired certificate) or inserted (e.g. [VerifyFlag::Other] to reject the certificate).
+    ///   Any flag left set fails the verification, and thus - unless the auth mode is [AuthMode::Optional] -
+    ///   the handshake.
+    fn verify(&self, cert: CertificateRef<'_>, depth: u32, flags: &mut EnumSet<VerifyFlag>);
+}
+
+impl core::fmt::Debug for dyn CertVerifier + '_ {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CertVerifier")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for dyn CertVerifier + '_ {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "CertVerifier")
+    }
+}
+
 /// Configuration for a TLS session
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -57,16 +244,104 @@ pub struct ClientSessionConfig<'a> {
     /// The CA chain should contain the trusted CA certificates
     /// that will be used to verify the server's certificate by the client during the handshake.
     pub ca_chain: Option<Certificate<'a>>,
+    /// Optional revocation list(s) of the CAs in `ca_chain`
+    ///
+    /// A revoked server certificate fails the handshake with [SessionError::CertificateRevoked].
+    pub crl: Option<Crl<'a>>,
     /// Optional client credentials used for authenticating the client to the server
     pub creds: Option<Credentials<'a>>,
+    /// Optional pre-shared key used for PSK authentication with the server
+    ///
+    /// Note that for PSK-only authentication, `ciphersuites` should be restricted to PSK ciphersuites
+    /// if TLS 1.2 is to be negotiated.
+    pub psk: Option<Psk<'a>>,
     /// The server name to verify in the certificate provided by the server
     /// Optional, because it can also be provided later
     pub server_name: Option<&'a CStr>,
     /// Certificate verification mode. Can be overriden.
     /// By default, [AuthMode::Required] will be used
     pub auth_mode: AuthMode,
+    /// Optional hook applying custom policy to the verification of the server certificate chain
+    pub verifier: Option<&'a dyn CertVerifier>,
+    /// The pins of the server certificate
+    ///
+    /// If not empty, the handshake fails with [SessionError::PinMismatch] unless the server certificate
+    /// matches at least one of the pins, regardless of `auth_mode`.
+    ///
+    /// Pinning can be used together with `ca_chain`, or instead of it, in which case the pins replace
+    /// the CA chain as the trust anchor, while all other checks (validity period, server name, etc.)
+    /// are still performed according to `auth_mode`.
+    pub pins: &'a [CertificatePin],
     /// The minimum TLS version that will be supported by a particular `Session` instance
     pub min_version: TlsVersion,
+    /// The maximum TLS version that will be supported by a particular `Session` instance
+    /// By default, [TlsVersion::Tls1_3] will be used
+    pub max_version: TlsVersion,
+    /// The ALPN protocols offered to the server, in order of preference (e.g. `h2`, `http/1.1`)
+    ///
+    /// An empty list (the default) disables ALPN.
+    /// At most [MAX_ALPN_PROTOCOLS] protocols are supported.
+    pub alpn_protocols: &'a [&'a CStr],
+    /// The ciphersuites offered to the server, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default ciphersuites are offered.
+    /// At most [MAX_CIPHERSUITES] ciphersuites are supported.
+    pub ciphersuites: &'a [Ciphersuite],
+    /// The (EC)DHE groups offered to the server, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default groups are offered.
+    /// At most [MAX_GROUPS] groups are supported.
+    pub groups: &'a [NamedGroup],
+    /// The signature algorithms offered to the server, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default signature algorithms are offered.
+    /// At most [MAX_SIG_ALGS] signature algorithms are supported.
+    pub sig_algs: &'a [SignatureAlgorithm],
+    /// Whether to offer TLS 1.3 early data (0-RTT) when resuming a session
+    ///
+    /// Early data can only be sent when resuming a session loaded with `load_session`,
+    /// whose ticket allows early data. By default, early data is not offered.
+    ///
+    /// Ignored if MbedTLS is built without `MBEDTLS_SSL_EARLY_DATA`.
+    pub early_data: bool,
+    /// Whether - and with which length - to negotiate DTLS connection IDs (RFC 9146) with the server
+    ///
+    /// With connection IDs, each record carries an ID chosen by its receiver, rather than the receiver relying
+    /// on the address of the sender. This way, the session survives changes of the client address (e.g. because
+    /// of NAT rebinding). `Some(len)` offers connection IDs to the server, `len` being the length of the (random) ID
+    /// the server has to put in its records to the client, which can be 0.
+    /// At most 32 bytes are supported. Ignored for TLS sessions. Requires MbedTLS to be built with
+    /// `MBEDTLS_SSL_DTLS_CONNECTION_ID`.
+    ///
+    /// If not set (the default), connection IDs are not offered.
+    pub connection_id_len: Option<u8>,
+    /// The maximum fragment length to request from the server (RFC 6066)
+    ///
+    /// If the server agrees, neither peer sends records with more than this many bytes of plaintext,
+    /// which allows the application to work with smaller buffers. Whether the server agreed is reported
+    /// by `ConnectionInfo::max_fragment_len`. The extension only exists up to TLS 1.2; with TLS 1.3 servers,
+    /// the record size limit extension (RFC 8449) is negotiated instead, announcing the size of the
+    /// incoming record buffer (`MBEDTLS_SSL_IN_CONTENT_LEN`) as the limit.
+    ///
+    /// Note that this does not shrink the record buffers of the session, which are sized at build time,
+    /// unless the `variable-buffer-length` feature is enabled.
+    ///
+    /// If not set (the default), no maximum fragment length is requested.
+    pub max_fragment_len: Option<MaxFragmentLength>,
+    /// The maximum length of the plaintext of the records sent to the server
+    ///
+    /// Unlike `max_fragment_len`, this is not negotiated: it limits the records the client sends on top of
+    /// the limits requested by the server. TLS writes larger than the limit are partial, while DTLS writes
+    /// larger than the limit are rejected with `MBEDTLS_ERR_SSL_BAD_INPUT_DATA`, as each is sent in a single record.
+    /// Values below 64 (the minimum of RFC 8449) are raised to 64.
+    ///
+    /// Note that the record size limit announced to TLS 1.3 servers (if MbedTLS is built with
+    /// `MBEDTLS_SSL_RECORD_SIZE_LIMIT`) is always the size of the incoming record buffer,
+    /// `MBEDTLS_SSL_IN_CONTENT_LEN`, which is set at build time with the `ESP_MBEDTLS_SSL_IN_CONTENT_LEN`
+    /// environment variable.
+    ///
+    /// If not set (the default), only the limits requested by the server apply.
+    pub max_write_len: Option<u16>,
 }
 
 impl<'a> Default for ClientSessionConfig<'a> {
@@ -79,10 +354,23 @@ impl<'a> ClientSessionConfig<'a> {
     pub const fn new() -> Self {
         Self {
             ca_chain: None,
+            crl: None,
             creds: None,
+            psk: None,
             server_name: None,
             auth_mode: AuthMode::Required,
+            verifier: None,
+            pins: &[],
             min_version: TlsVersion::Tls1_2,
+            max_version: TlsVersion::Tls1_3,
+            alpn_protocols: &[],
+            ciphersuites: &[],
+            groups: &[],
+            sig_algs: &[],
+            early_data: false,
+            connection_id_len: None,
+            max_fragment_len: None,
+            max_write_len: None,
         }
     }
 }
@@ -96,22 +384,130 @@ pub struct ServerSessionConfig<'a> {
     /// The CA chain should contain the trusted CA certificates
     /// that will be used to verify the client's certificate by the server during the handshake.
     pub ca_chain: Option<Certificate<'a>>,
+    /// Optional revocation list(s) of the CAs in `ca_chain`
+    ///
+    /// A revoked client certificate fails the handshake with [SessionError::CertificateRevoked].
+    pub crl: Option<Crl<'a>>,
     /// Server credentials used for authenticating the server to the client
+    ///
+    /// These are the default credentials, used if the client did not request a server name (SNI),
+    /// or if `sni_resolver` is not set.
     pub creds: Credentials<'a>,
+    /// Optional hook selecting the server credentials based on the server name (SNI) requested by the client
+    pub sni_resolver: Option<&'a dyn SniResolver>,
+    /// Optional hook resolving the pre-shared keys of clients using PSK authentication
+    ///
+    /// If not set, PSK authentication is not supported by the server.
+    pub psk_lookup: Option<&'a dyn PskLookup>,
+    /// Optional session ticket key manager, enabling the issuance of session tickets to clients
+    ///
+    /// If not set, session tickets are not issued and thus sessions cannot be resumed with tickets.
+    pub session_tickets: Option<SessionTickets<'a>>,
+    /// Optional session cache, enabling session ID-based resumption of TLS 1.2 sessions
+    ///
+    /// If not set, sessions cannot be resumed by session ID.
+    pub session_cache: Option<SessionCache>,
+    /// Optional DTLS cookie manager, enabling the HelloVerifyRequest exchange with the clients
+    ///
+    /// If not set, DTLS server sessions start a handshake with any client without verifying its address first.
+    /// The exception is `DtlsAcceptor`, which then uses a cookie manager of its own.
+    /// Ignored for TLS sessions. Requires MbedTLS to be built with `MBEDTLS_SSL_DTLS_HELLO_VERIFY`.
+    pub dtls_cookies: Option<DtlsCookies<'a>>,
     /// Client certificate verification mode. Can be overriden.
     /// By default, [AuthMode::None] will be used
     pub auth_mode: AuthMode,
+    /// Optional hook applying custom policy to the verification of the client certificate chain
+    pub verifier: Option<&'a dyn CertVerifier>,
     /// The minimum TLS version that will be supported by a particular `Session` instance
     pub min_version: TlsVersion,
+    /// The maximum TLS version that will be supported by a particular `Session` instance
+    /// By default, [TlsVersion::Tls1_3] will be used
+    pub max_version: TlsVersion,
+    /// The ALPN protocols supported by the server, in order of preference
+    ///
+    /// An empty list (the default) disables ALPN.
+    /// At most [MAX_ALPN_PROTOCOLS] protocols are supported.
+    pub alpn_protocols: &'a [&'a CStr],
+    /// The ciphersuites accepted from the client, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default ciphersuites are accepted.
+    /// At most [MAX_CIPHERSUITES] ciphersuites are supported.
+    pub ciphersuites: &'a [Ciphersuite],
+    /// The (EC)DHE groups accepted from the client, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default groups are accepted.
+    /// At most [MAX_GROUPS] groups are supported.
+    pub groups: &'a [NamedGroup],
+    /// The signature algorithms accepted from the client, in order of preference
+    ///
+    /// An empty list (the default) means that the MbedTLS default signature algorithms are accepted.
+    /// At most [MAX_SIG_ALGS] signature algorithms are supported.
+    pub sig_algs: &'a [SignatureAlgorithm],
+    /// The maximum amount of TLS 1.3 early data (0-RTT) accepted from a client resuming a session, in bytes
+    ///
+    /// If set, the session tickets issued to clients allow early data, which can then be read
+    /// with `read_early_data`. Requires `session_tickets` to be set as well.
+    ///
+    /// If not set (the default), or if MbedTLS is built without `MBEDTLS_SSL_EARLY_DATA`, early data is rejected.
+    pub max_early_data_size: Option<u32>,
+    /// Whether - and with which length - to negotiate DTLS connection IDs (RFC 9146) with the clients
+    ///
+    /// With connection IDs, each record carries an ID chosen by its receiver, rather than the receiver relying
+    /// on the address of the sender. This way, a `DtlsAcceptor` keeps serving clients whose address changed
+    /// (e.g. because of NAT rebinding). `Some(len)` accepts the connection IDs offered by the clients, `len` being
+    /// the length of the (random) ID the clients have to put in their records to the server.
+    /// At most 32 bytes are supported. Ignored for TLS sessions. Requires MbedTLS to be built with
+    /// `MBEDTLS_SSL_DTLS_CONNECTION_ID`.
+    ///
+    /// If not set (the default), connection IDs are not negotiated.
+    pub connection_id_len: Option<u8>,
+    /// The maximum fragment length of the records sent to the clients
+    ///
+    /// The maximum fragment length requested by a client (RFC 6066) is always honored; this additionally
+    /// limits the plaintext carried by the records the server sends, whether the client requested a limit or not.
+    /// With TLS 1.3 clients, the record size limit extension (RFC 8449) is negotiated as well, announcing the size
+    /// of the incoming record buffer (`MBEDTLS_SSL_IN_CONTENT_LEN`) as the limit.
+    ///
+    /// If not set (the default), only the limits requested by the clients apply.
+    pub max_fragment_len: Option<MaxFragmentLength>,
+    /// The maximum length of the plaintext of the records sent to the clients
+    ///
+    /// Like `max_fragment_len`, but not restricted to the lengths of RFC 6066. TLS writes larger than the limit
+    /// are partial, while DTLS writes larger than the limit are rejected with `MBEDTLS_ERR_SSL_BAD_INPUT_DATA`,
+    /// as each is sent in a single record. Values below 64 (the minimum of RFC 8449) are raised to 64.
+    ///
+    /// Note that the record size limit announced to TLS 1.3 clients (if MbedTLS is built with
+    /// `MBEDTLS_SSL_RECORD_SIZE_LIMIT`) is always the size of the incoming record buffer,
+    /// `MBEDTLS_SSL_IN_CONTENT_LEN`, which is set at build time with the `ESP_MBEDTLS_SSL_IN_CONTENT_LEN`
+    /// environment variable.
+    ///
+    /// If not set (the default), only the limits requested by the clients apply.
+    pub max_write_len: Option<u16>,
 }
 
 impl<'a> ServerSessionConfig<'a> {
     pub const fn new(creds: Credentials<'a>) -> Self {
         Self {
             ca_chain: None,
+            crl: None,
             creds,
+            sni_resolver: None,
+            psk_lookup: None,
+            session_tickets: None,
+            session_cache: None,
+            dtls_cookies: None,
             auth_mode: AuthMode::None,
+            verifier: None,
             min_version: TlsVersion::Tls1_2,
+            max_version: TlsVersion::Tls1_3,
+            alpn_protocols: &[],
+            ciphersuites: &[],
+            groups: &[],
+            sig_algs: &[],
+            max_early_data_size: None,
+            connection_id_len: None,
+            max_fragment_len: None,
+            max_write_len: None,
         }
     }
 }
@@ -132,6 +528,13 @@ impl<'a> SessionConfig<'a> {
         }
     }
 
+    fn crl(&self) -> Option<&Crl<'a>> {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { crl, .. }) => crl.as_ref(),
+            SessionConfig::Server(ServerSessionConfig { crl, .. }) => crl.as_ref(),
+        }
+    }
+
     fn creds(&self) -> Option<&Credentials<'a>> {
         match self {
             SessionConfig::Client(ClientSessionConfig { creds, .. }) => creds.as_ref(),
@@ -146,6 +549,20 @@ impl<'a> SessionConfig<'a> {
         }
     }
 
+    fn verifier(&self) -> Option<&'a dyn CertVerifier> {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { verifier, .. }) => *verifier,
+            SessionConfig::Server(ServerSessionConfig { verifier, .. }) => *verifier,
+        }
+    }
+
+    fn pins(&self) -> &'a [CertificatePin] {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { pins, .. }) => pins,
+            SessionConfig::Server(_) => &[],
+        }
+    }
+
     fn min_version(&self) -> TlsVersion {
         match self {
             SessionConfig::Client(ClientSessionConfig { min_version, .. }) => *min_version,
@@ -153,6 +570,70 @@ impl<'a> SessionConfig<'a> {
         }
     }
 
+    fn max_version(&self) -> TlsVersion {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { max_version, .. }) => *max_version,
+            SessionConfig::Server(ServerSessionConfig { max_version, .. }) => *max_version,
+        }
+    }
+
+    fn alpn_protocols(&self) -> &'a [&'a CStr] {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { alpn_protocols, .. }) => alpn_protocols,
+            SessionConfig::Server(ServerSessionConfig { alpn_protocols, .. }) => alpn_protocols,
+        }
+    }
+
+    fn ciphersuites(&self) -> &'a [Ciphersuite] {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { ciphersuites, .. }) => ciphersuites,
+            SessionConfig::Server(ServerSessionConfig { ciphersuites, .. }) => ciphersuites,
+        }
+    }
+
+    fn groups(&self) -> &'a [NamedGroup] {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { groups, .. }) => groups,
+            SessionConfig::Server(ServerSessionConfig { groups, .. }) => groups,
+        }
+    }
+
+    fn sig_algs(&self) -> &'a [SignatureAlgorithm] {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { sig_algs, .. }) => sig_algs,
+            SessionConfig::Server(ServerSessionConfig { sig_algs, .. }) => sig_algs,
+        }
+    }
+
+    fn connection_id_len(&self) -> Option<u8> {
+        match self {
+            SessionConfig::Client(ClientSessionConfig {
+                connection_id_len, ..
+            }) => *connection_id_len,
+            SessionConfig::Server(ServerSessionConfig {
+                connection_id_len, ..
+            }) => *connection_id_len,
+        }
+    }
+
+    fn max_fragment_len(&self) -> Option<MaxFragmentLength> {
+        match self {
+            SessionConfig::Client(ClientSessionConfig {
+                max_fragment_len, ..
+            }) => *max_fragment_len,
+            SessionConfig::Server(ServerSessionConfig {
+                max_fragment_len, ..
+            }) => *max_fragment_len,
+        }
+    }
+
+    fn max_write_len(&self) -> Option<u16> {
+        match self {
+            SessionConfig::Client(ClientSessionConfig { max_write_len, .. }) => *max_write_len,
+            SessionConfig::Server(ServerSessionConfig { max_write_len, .. }) => *max_write_len,
+        }
+    }
+
     fn raw_mode(&self) -> c_int {
         match self {
             Self::Client { .. } => MBEDTLS_SSL_IS_CLIENT as c_int,
@@ -161,6 +642,18 @@ impl<'a> SessionConfig<'a> {
     }
 }
 
+/// The maximum number of ALPN protocols that can be configured for a session
+pub const MAX_ALPN_PROTOCOLS: usize = 8;
+
+/// The maximum number of ciphersuites that can be configured for a session
+pub const MAX_CIPHERSUITES: usize = 32;
+
+/// The maximum number of (EC)DHE groups that can be configured for a session
+pub const MAX_GROUPS: usize = 16;
+
+/// The maximum number of signature algorithms that can be configured for a session
+pub const MAX_SIG_ALGS: usize = 16;
+
 /// Session state
 struct SessionState<'a> {
     /// The SSL context
@@ -180,16 +673,100 @@ struct SessionState<'a> {
     /// While not explicitly used, we need to keep a reference to it as it is used
     /// by the SSL context via a raw pointer
     _ca_chain: Option<Certificate<'a>>,
+    /// The CRL
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _crl: Option<Crl<'a>>,
     /// The credentials
     ///
     /// While not explicitly used, we need to keep a reference to it as it is used
     /// by the SSL context via a raw pointer
     _creds: Option<Credentials<'a>>,
+    /// The zero-terminated list of ALPN protocols
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _alpn_protocols: Option<MBox<MList<*const c_char, { MAX_ALPN_PROTOCOLS + 1 }>>>,
+    /// The zero-terminated list of ciphersuite IDs
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _ciphersuites: Option<MBox<MList<c_int, { MAX_CIPHERSUITES + 1 }>>>,
+    /// The zero-terminated list of group IDs
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _groups: Option<MBox<MList<u16, { MAX_GROUPS + 1 }>>>,
+    /// The zero-terminated list of signature algorithm IDs
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _sig_algs: Option<MBox<MList<u16, { MAX_SIG_ALGS + 1 }>>>,
+    /// The certificate verification state
+    ///
+    /// Used by the SSL context via a raw pointer
+    verify: Option<MBox<VerifyContext<'a>>>,
+    /// The server PSK lookup hook
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _psk_lookup: Option<MBox<MCallback<'a, dyn PskLookup + 'a>>>,
+    /// The server name (SNI) state
+    ///
+    /// Used by the SSL context via a raw pointer
+    sni: Option<MBox<SniContext<'a>>>,
+    /// The session ticket key manager
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _session_tickets: Option<SessionTickets<'a>>,
+    /// The session cache
+    ///
+    /// While not explicitly used, we need to keep a reference to it as it is used
+    /// by the SSL context via a raw pointer
+    _session_cache: Option<SessionCache>,
+    /// The DTLS cookie manager, for DTLS server sessions
+    ///
+    /// Used by the SSL context via a raw pointer
+    dtls_cookies: Option<DtlsCookies<'a>>,
+    /// The session to be resumed by the next handshake, if any
+    resumption_session: Option<MBox<mbedtls_ssl_session>>,
+    /// The session last exported from the SSL context, if any
+    ///
+    /// MbedTLS allows exporting a session only once (i.e. once per TLS 1.3 session ticket),
+    /// so the exported session is kept around for subsequent saves
+    exported_session: Option<MBox<mbedtls_ssl_session>>,
+    /// The parameters of the last handshake which are not retained by the SSL context
+    handshake: HandshakeInfo,
+    /// Whether a handshake was started - by writing or reading early data - but is not complete yet
+    in_handshake: bool,
+    /// The DTLS retransmission timer, for DTLS sessions
+    ///
+    /// Used by the SSL context via a raw pointer
+    retransmission: Option<MBox<RetransmissionTimer>>,
+    /// The configured maximum length of the plaintext of the records sent to the peer, if any
+    max_write_len: Option<u16>,
 }
 
 impl<'a> SessionState<'a> {
     /// Initialize the Session state using the given configuration
-    fn new(conf: &SessionConfig<'a>) -> Result<Self, MbedtlsError> {
+    ///
+    /// # Arguments
+    /// - `conf` - The session configuration
+    /// - `datagram` - Whether the session runs over a datagram transport (DTLS) rather than over a stream (TLS)
+    fn new(conf: &SessionConfig<'a>, datagram: bool) -> Result<Self, SessionError> {
+        // MbedTLS does not support DTLS 1.3, so DTLS sessions are capped at (D)TLS 1.2
+        let max_version = if datagram {
+            conf.max_version().min(TlsVersion::Tls1_2)
+        } else {
+            conf.max_version()
+        };
+
+        if conf.min_version() > max_version {
+            return Err(SessionError::InvalidVersionRange);
+        }
+
         merr!(unsafe { psa_crypto_init() })?;
 
         let mut ssl_config = MBox::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
@@ -198,7 +775,11 @@ impl<'a> SessionState<'a> {
             mbedtls_ssl_config_defaults(
                 &mut *ssl_config,
                 conf.raw_mode(),
-                MBEDTLS_SSL_TRANSPORT_STREAM as i32,
+                if datagram {
+                    MBEDTLS_SSL_TRANSPORT_DATAGRAM as i32
+                } else {
+                    MBEDTLS_SSL_TRANSPORT_STREAM as i32
+                },
                 MBEDTLS_SSL_PRESET_DEFAULT as i32,
             )
         })?;
@@ -206,11 +787,25 @@ impl<'a> SessionState<'a> {
         // Set the minimum TLS version
         // Use a direct field modified for compatibility with the `esp-idf-svc` mbedtls
         ssl_config.private_min_tls_version = conf.min_version().mbed_tls_version();
+        // Ditto for the maximum TLS version
+        ssl_config.private_max_tls_version = max_version.mbed_tls_version();
 
         Tls::hook_debug_logs(&mut ssl_config);
 
+        let pins = conf.pins();
+        let pins_only = !pins.is_empty() && conf.ca_chain().is_none();
+
+        let auth_mode = match conf.auth_mode() {
+            AuthMode::Required if !pins_only => AuthMode::Required,
+            // Without a CA chain, MbedTLS fails a required verification upfront, and
+            // with no verification at all, the pins would not be checked.
+            // Hence the verification is done in optional mode and enforced by the verification callback
+            _ if !pins.is_empty() => AuthMode::Optional,
+            auth_mode => auth_mode,
+        };
+
         unsafe {
-            mbedtls_ssl_conf_authmode(&mut *ssl_config, conf.auth_mode().mbedtls_authmode());
+            mbedtls_ssl_conf_authmode(&mut *ssl_config, auth_mode.mbedtls_authmode());
         }
 
         if let Some(creds) = conf.creds() {
@@ -228,81 +823,1470 @@ impl<'a> SessionState<'a> {
                 mbedtls_ssl_conf_ca_chain(
                     &mut *ssl_config,
                     &*ca_chain.crt as *const _ as *mut _,
-                    core::ptr::null_mut(),
+                    conf.crl()
+                        .map(|crl| &*crl.crl as *const _ as *mut _)
+                        .unwrap_or(core::ptr::null_mut()),
                 );
             }
         }
 
-        let mut drbg_context =
-            MBox::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
-
-        // Init RNG
-        unsafe {
-            mbedtls_ssl_conf_rng(
-                &mut *ssl_config,
-                Some(mbedtls_rng),
-                &mut *drbg_context as *mut _ as *mut c_void,
-            );
-        }
+        // Without a time source, the verification callback is needed for ignoring the validity periods
+        let verify =
+            if cfg!(not(target_os = "espidf")) || conf.verifier().is_some() || !pins.is_empty() {
+                let mut verify = MBox::new_with(VerifyContext {
+                    verifier: conf.verifier(),
+                    pins: (!pins.is_empty()).then_some(pins),
+                    pins_only,
+                    enforce: pins_only && conf.auth_mode() == AuthMode::Required,
+                    flags: 0,
+                    error: None,
+                })
+                .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
 
-        let mut ssl_context = MBox::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+                unsafe {
+                    mbedtls_ssl_conf_verify(
+                        &mut *ssl_config,
+                        Some(verify_callback),
+                        &mut *verify as *mut VerifyContext as *mut c_void,
+                    );
+                }
 
-        merr!(unsafe { mbedtls_ssl_setup(&mut *ssl_context, &*ssl_config) })?;
+                Some(verify)
+            } else {
+                None
+            };
 
-        if let SessionConfig::Client(conf) = conf {
-            if let Some(server_name) = conf.server_name {
+        let psk_lookup = match conf {
+            SessionConfig::Client(ClientSessionConfig { psk: Some(psk), .. }) => {
                 merr!(unsafe {
-                    mbedtls_ssl_set_hostname(&mut *ssl_context, server_name.as_ptr())
+                    mbedtls_ssl_conf_psk(
+                        &mut *ssl_config,
+                        psk.key.as_ptr(),
+                        psk.key.len(),
+                        psk.identity.as_ptr(),
+                        psk.identity.len(),
+                    )
                 })?;
+
+                None
             }
-        }
+            SessionConfig::Server(ServerSessionConfig {
+                psk_lookup: Some(psk_lookup),
+                ..
+            }) => {
+                let psk_lookup = MCallback::new(*psk_lookup)?;
 
-        Ok(Self {
-            ssl_context,
-            _drbg: drbg_context,
-            _ssl_config: ssl_config,
-            _ca_chain: conf.ca_chain().cloned(),
-            _creds: conf.creds().cloned(),
-        })
-    }
-}
+                unsafe {
+                    mbedtls_ssl_conf_psk_cb(
+                        &mut *ssl_config,
+                        Some(psk_lookup_callback),
+                        psk_lookup.as_ptr(),
+                    );
+                }
 
-/// Error type for session operations
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SessionError {
-    /// MBedTLS error
-    MbedTls(MbedtlsError),
-    /// IO error
-    Io(ErrorKind),
-}
+                Some(psk_lookup)
+            }
+            _ => None,
+        };
 
-impl SessionError {
-    /// Create a SessionError from an embedded-io Error
-    pub fn from_io<E: Error>(err: E) -> Self {
-        Self::Io(err.kind())
-    }
-}
+        let sni = if let SessionConfig::Server(conf) = conf {
+            let mut sni =
+                MBox::<SniContext>::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
 
-impl From<MbedtlsError> for SessionError {
-    fn from(e: MbedtlsError) -> Self {
-        Self::MbedTls(e)
-    }
-}
+            sni.resolver = conf.sni_resolver;
 
-impl From<ErrorKind> for SessionError {
-    fn from(e: ErrorKind) -> Self {
-        Self::Io(e)
-    }
-}
+            // Registered even without a resolver, as this is the only way to capture the server name
+            unsafe {
+                mbedtls_ssl_conf_sni(
+                    &mut *ssl_config,
+                    Some(sni_callback),
+                    &mut *sni as *mut _ as *mut c_void,
+                );
+            }
 
-impl core::fmt::Display for SessionError {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Self::MbedTls(e) => write!(f, "{}", e),
-            Self::Io(e) => write!(f, "IO({:?})", e),
-        }
-    }
-}
+            Some(sni)
+        } else {
+            None
+        };
+
+        let session_tickets = if let SessionConfig::Server(ServerSessionConfig {
+            session_tickets: Some(session_tickets),
+            ..
+        }) = conf
+        {
+            unsafe {
+                mbedtls_ssl_conf_session_tickets_cb(
+                    &mut *ssl_config,
+                    Some(mbedtls_ssl_ticket_write),
+                    Some(mbedtls_ssl_ticket_parse),
+                    session_tickets.as_ptr(),
+                );
+            }
+
+            Some(session_tickets.clone())
+        } else {
+            None
+        };
+
+        let session_cache = if let SessionConfig::Server(ServerSessionConfig {
+            session_cache: Some(session_cache),
+            ..
+        }) = conf
+        {
+            unsafe {
+                mbedtls_ssl_conf_session_cache(
+                    &mut *ssl_config,
+                    session_cache.as_ptr(),
+                    Some(session_cache_get),
+                    Some(session_cache_set),
+                );
+            }
+
+            Some(session_cache.clone())
+        } else {
+            None
+        };
+
+        if let SessionConfig::Client(_) = conf {
+            // Have MbedTLS process - rather than ignore - the TLS 1.3 session tickets sent by the server,
+            // so that these can be exported with `save_session`
+            unsafe {
+                mbedtls_ssl_conf_tls13_enable_signal_new_session_tickets(
+                    &mut *ssl_config,
+                    MBEDTLS_SSL_TLS1_3_SIGNAL_NEW_SESSION_TICKETS_ENABLED as c_int,
+                );
+            }
+        }
+
+        #[cfg(mbedtls_ssl_early_data)]
+        match conf {
+            SessionConfig::Client(ClientSessionConfig {
+                early_data: true, ..
+            }) => unsafe {
+                mbedtls_ssl_conf_early_data(
+                    &mut *ssl_config,
+                    MBEDTLS_SSL_EARLY_DATA_ENABLED as c_int,
+                );
+            },
+            SessionConfig::Server(ServerSessionConfig {
+                max_early_data_size: Some(max_early_data_size),
+                ..
+            }) => unsafe {
+                mbedtls_ssl_conf_early_data(
+                    &mut *ssl_config,
+                    MBEDTLS_SSL_EARLY_DATA_ENABLED as c_int,
+                );
+                mbedtls_ssl_conf_max_early_data_size(&mut *ssl_config, *max_early_data_size);
+            },
+            _ => (),
+        }
+
+        #[cfg(mbedtls_ssl_dtls_hello_verify)]
+        let dtls_cookies = match conf {
+            SessionConfig::Server(ServerSessionConfig {
+                dtls_cookies: Some(dtls_cookies),
+                ..
+            }) if datagram => {
+                unsafe {
+                    mbedtls_ssl_conf_dtls_cookies(
+                        &mut *ssl_config,
+                        Some(mbedtls_ssl_cookie_write),
+                        Some(mbedtls_ssl_cookie_check),
+                        dtls_cookies.as_ptr(),
+                    );
+                }
+
+                Some(dtls_cookies.clone())
+            }
+            SessionConfig::Server(_) if datagram => {
+                // MbedTLS enables HelloVerifyRequest cookies for DTLS servers by default, but with
+                // callbacks which always fail, so these need to be disabled unless proper ones are set
+                unsafe {
+                    mbedtls_ssl_conf_dtls_cookies(
+                        &mut *ssl_config,
+                        None,
+                        None,
+                        core::ptr::null_mut(),
+                    );
+                }
+
+                None
+            }
+            _ => None,
+        };
+
+        #[cfg(not(mbedtls_ssl_dtls_hello_verify))]
+        let dtls_cookies = match conf {
+            SessionConfig::Server(ServerSessionConfig {
+                dtls_cookies: Some(_),
+                ..
+            }) if datagram => Err(MbedtlsError::new(MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE))?,
+            _ => None,
+        };
+
+        let connection_id_len = conf.connection_id_len().filter(|_| datagram);
+
+        #[cfg(not(mbedtls_ssl_dtls_connection_id))]
+        if connection_id_len.is_some() {
+            Err(MbedtlsError::new(MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE))?;
+        }
+
+        #[cfg(mbedtls_ssl_dtls_connection_id)]
+        if let Some(len) = connection_id_len {
+            merr!(unsafe {
+                mbedtls_ssl_conf_cid(
+                    &mut *ssl_config,
+                    len as usize,
+                    MBEDTLS_SSL_UNEXPECTED_CID_IGNORE as c_int,
+                )
+            })?;
+        }
+
+        if let Some(max_fragment_len) = conf.max_fragment_len() {
+            merr!(unsafe {
+                mbedtls_ssl_conf_max_frag_len(&mut *ssl_config, max_fragment_len.mbedtls_code())
+            })?;
+        }
+
+        let alpn_protocols = if !conf.alpn_protocols().is_empty() {
+            let alpn_protocols = MList::new(
+                conf.alpn_protocols()
+                    .iter()
+                    .map(|protocol| protocol.as_ptr()),
+            )?;
+
+            merr!(unsafe {
+                mbedtls_ssl_conf_alpn_protocols(&mut *ssl_config, alpn_protocols.as_ptr() as *mut _)
+            })?;
+
+            Some(alpn_protocols)
+        } else {
+            None
+        };
+
+        let ciphersuites = if !conf.ciphersuites().is_empty() {
+            let ciphersuites = MList::new(
+                conf.ciphersuites()
+                    .iter()
+                    .map(|ciphersuite| ciphersuite.id() as c_int),
+            )?;
+
+            unsafe {
+                mbedtls_ssl_conf_ciphersuites(&mut *ssl_config, ciphersuites.as_ptr());
+            }
+
+            Some(ciphersuites)
+        } else {
+            None
+        };
+
+        let groups = if !conf.groups().is_empty() {
+            let groups = MList::new(conf.groups().iter().map(NamedGroup::id))?;
+
+            unsafe {
+                mbedtls_ssl_conf_groups(&mut *ssl_config, groups.as_ptr());
+            }
+
+            Some(groups)
+        } else {
+            None
+        };
+
+        let sig_algs = if !conf.sig_algs().is_empty() {
+            let sig_algs = MList::new(conf.sig_algs().iter().map(SignatureAlgorithm::id))?;
+
+            unsafe {
+                mbedtls_ssl_conf_sig_algs(&mut *ssl_config, sig_algs.as_ptr());
+            }
+
+            Some(sig_algs)
+        } else {
+            None
+        };
+
+        let mut drbg_context =
+            MBox::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        // Init RNG
+        unsafe {
+            mbedtls_ssl_conf_rng(
+                &mut *ssl_config,
+                Some(mbedtls_rng),
+                &mut *drbg_context as *mut _ as *mut c_void,
+            );
+        }
+
+        let mut ssl_context = MBox::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        merr!(unsafe { mbedtls_ssl_setup(&mut *ssl_context, &*ssl_config) })?;
+
+        let retransmission = if datagram {
+            let mut retransmission = MBox::<RetransmissionTimer>::new()
+                .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+            unsafe {
+                mbedtls_ssl_set_timer_cb(
+                    &mut *ssl_context,
+                    &mut *retransmission as *mut _ as *mut c_void,
+                    Some(set_timer_callback),
+                    Some(get_timer_callback),
+                );
+            }
+
+            Some(retransmission)
+        } else {
+            None
+        };
+
+        #[cfg(mbedtls_ssl_dtls_connection_id)]
+        if let Some(len) = connection_id_len {
+            set_random_cid(&mut ssl_context, len as usize)?;
+        }
+
+        if let SessionConfig::Client(conf) = conf {
+            if let Some(server_name) = conf.server_name {
+                merr!(unsafe {
+                    mbedtls_ssl_set_hostname(&mut *ssl_context, server_name.as_ptr())
+                })?;
+            }
+        }
+
+        Ok(Self {
+            ssl_context,
+            _drbg: drbg_context,
+            _ssl_config: ssl_config,
+            _ca_chain: conf.ca_chain().cloned(),
+            _crl: conf.crl().cloned(),
+            _creds: conf.creds().cloned(),
+            _alpn_protocols: alpn_protocols,
+            _ciphersuites: ciphersuites,
+            _groups: groups,
+            _sig_algs: sig_algs,
+            verify,
+            _psk_lookup: psk_lookup,
+            sni,
+            _session_tickets: session_tickets,
+            _session_cache: session_cache,
+            dtls_cookies,
+            resumption_session: None,
+            exported_session: None,
+            handshake: HandshakeInfo::default(),
+            in_handshake: false,
+            retransmission,
+            max_write_len: conf
+                .max_write_len()
+                .map(|limit| limit.max(MIN_RECORD_SIZE_LIMIT)),
+        })
+    }
+
+    /// Reset the SSL context in preparation for a new handshake
+    ///
+    /// If a session to be resumed had been loaded, it is set on the SSL context.
+    fn reset(&mut self) -> Result<(), SessionError> {
+        merr!(unsafe { mbedtls_ssl_session_reset(&mut *self.ssl_context) })?;
+
+        if let Some(sni) = self.sni.as_mut() {
+            sni.len = 0;
+        }
+
+        if let Some(verify) = self.verify.as_mut() {
+            verify.flags = 0;
+            verify.error = None;
+        }
+
+        self.handshake = HandshakeInfo::default();
+
+        if let Some(session) = self.resumption_session.as_ref() {
+            merr!(unsafe { mbedtls_ssl_set_session(&mut *self.ssl_context, &**session) })?;
+        }
+
+        Ok(())
+    }
+
+    /// Bind the DTLS HelloVerifyRequest cookies to the address of the client
+    ///
+    /// To be called after each reset, as MbedTLS forgets the address on reset.
+    /// No-op for sessions without cookies.
+    fn set_client_addr(&mut self, addr: SocketAddr) -> Result<(), SessionError> {
+        if self.dtls_cookies.is_some() {
+            merr!(unsafe { set_client_transport_id(&mut *self.ssl_context, addr) })?;
+        }
+
+        Ok(())
+    }
+
+    /// Record the current time for the DTLS retransmission timer
+    ///
+    /// To be called before each call into MbedTLS, as well as after each blocking wait
+    /// from within the BIO callbacks. No-op for TLS sessions.
+    fn sample_time(&mut self, now: Duration) {
+        if let Some(retransmission) = self.retransmission.as_mut() {
+            retransmission.now = now;
+        }
+    }
+
+    /// Get the time by which MbedTLS has to be called again so that it retransmits
+    /// its last DTLS handshake flight, if the retransmission timer is armed
+    #[cfg(feature = "edge-nal")]
+    fn retransmission_deadline(&self) -> Option<Duration> {
+        self.retransmission
+            .as_ref()
+            .filter(|retransmission| retransmission.fin_ms > 0)
+            .map(|retransmission| {
+                retransmission.start + Duration::from_millis(retransmission.fin_ms as _)
+            })
+    }
+
+    /// Shorten the data of a TLS write to the configured maximum write length, if any
+    ///
+    /// The rest of the data is left to the next writes, as TLS writes may be partial.
+    fn limit_write<'d>(&self, data: &'d [u8]) -> &'d [u8] {
+        &data[..limit_write_len(data.len(), self.max_write_len)]
+    }
+
+    /// Check that the data of a DTLS write fits in the configured maximum write length, if any
+    ///
+    /// # Returns
+    /// - `MBEDTLS_ERR_SSL_BAD_INPUT_DATA` if the data is larger than the limit, as DTLS writes
+    ///   are sent in a single record
+    fn check_datagram_write(&self, data: &[u8]) -> Result<(), SessionError> {
+        if limit_write_len(data.len(), self.max_write_len) < data.len() {
+            Err(MbedtlsError::new(MBEDTLS_ERR_SSL_BAD_INPUT_DATA))?;
+        }
+
+        Ok(())
+    }
+
+    /// Take a snapshot of the parameters negotiated with the peer
+    fn connection_info(&self) -> ConnectionInfo {
+        let ssl_context = &*self.ssl_context;
+
+        // For client sessions, the server name set with `mbedtls_ssl_set_hostname`
+        let hostname = unsafe { ssl_context.private_hostname.as_ref() }
+            .map(|hostname| unsafe { CStr::from_ptr(hostname) }.to_bytes());
+
+        let server_name = self
+            .sni
+            .as_ref()
+            .and_then(|sni| sni.server_name())
+            .map(str::as_bytes)
+            .or(hostname)
+            .filter(|name| name.len() <= MAX_SERVER_NAME_LEN)
+            .unwrap_or(&[]);
+
+        let alpn = negotiated_alpn(ssl_context)
+            .map(CStr::to_bytes)
+            .filter(|alpn| alpn.len() <= MAX_ALPN_LEN)
+            .unwrap_or(&[]);
+
+        let mut info = ConnectionInfo {
+            version: negotiated_version(ssl_context),
+            ciphersuite: negotiated_ciphersuite(ssl_context),
+            group: self.handshake.group,
+            resumed: !self.handshake.full,
+            peer_fingerprint: peer_certificate(ssl_context).map(|cert| cert.fingerprint()),
+            record_expansion: usize::try_from(unsafe {
+                mbedtls_ssl_get_record_expansion(ssl_context)
+            })
+            .ok(),
+            max_record_payload: usize::try_from(unsafe {
+                mbedtls_ssl_get_max_out_record_payload(ssl_context)
+            })
+            .ok()
+            .map(|max| limit_write_len(max, self.max_write_len)),
+            max_in_record_payload: usize::try_from(unsafe {
+                mbedtls_ssl_get_max_in_record_payload(ssl_context)
+            })
+            .ok(),
+            max_fragment_len: unsafe { ssl_context.private_session.as_ref() }
+                .and_then(|session| MaxFragmentLength::from_mbedtls_code(session.private_mfl_code)),
+            peer_record_size_limit: peer_record_size_limit(ssl_context),
+            alpn_len: alpn.len(),
+            alpn: [0; MAX_ALPN_LEN],
+            server_name_len: server_name.len(),
+            server_name: [0; MAX_SERVER_NAME_LEN],
+        };
+
+        info.alpn[..alpn.len()].copy_from_slice(alpn);
+        info.server_name[..server_name.len()].copy_from_slice(server_name);
+
+        info
+    }
+
+    /// Map a failed handshake to the certificate verification error recorded by the
+    /// verification callback, or to a revoked peer certificate, if any
+    fn handshake_error(&mut self, err: SessionError) -> SessionError {
+        if let Some(err) = self.verify.as_mut().and_then(|verify| verify.error.take()) {
+            return err;
+        }
+
+        let revoked = unsafe { mbedtls_ssl_get_verify_result(&*self.ssl_context) }
+            & MBEDTLS_X509_BADCERT_REVOKED
+            != 0;
+
+        if err == SessionError::MbedTls(MbedtlsError::new(MBEDTLS_ERR_X509_CERT_VERIFY_FAILED))
+            && revoked
+        {
+            SessionError::CertificateRevoked
+        } else {
+            err
+        }
+    }
+
+    /// Serialize the session negotiated with the server into the provided buffer
+    fn save_session(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        let exportable = unsafe { self.ssl_context.private_session.as_ref() }
+            .is_some_and(|session| session.private_exported == 0);
+
+        if exportable {
+            let mut session = MBox::<mbedtls_ssl_session>::new()
+                .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+            merr!(unsafe { mbedtls_ssl_get_session(&*self.ssl_context, &mut *session) })?;
+
+            self.exported_session = Some(session);
+        }
+
+        let session = self
+            .exported_session
+            .as_ref()
+            .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_BAD_INPUT_DATA))?;
+
+        let mut len = 0;
+
+        merr!(unsafe {
+            mbedtls_ssl_session_save(&**session, buf.as_mut_ptr(), buf.len(), &mut len)
+        })?;
+
+        Ok(len)
+    }
+
+    /// Deserialize a session previously saved with `save_session`,
+    /// so that it is resumed by the next handshake
+    fn load_session(&mut self, data: &[u8]) -> Result<(), SessionError> {
+        let mut session = MBox::<mbedtls_ssl_session>::new()
+            .ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        merr!(unsafe { mbedtls_ssl_session_load(&mut *session, data.as_ptr(), data.len()) })?;
+
+        self.resumption_session = Some(session);
+
+        Ok(())
+    }
+}
+
+/// The certificate verification state of a session
+///
+/// Meant to be allocated with `MBox::new_with` so that its address is stable for as long as MbedTLS keeps
+/// a pointer to it. Unlike the other contexts, it is not valid when zeroed, because of `error`.
+struct VerifyContext<'a> {
+    /// The hook applying custom verification policy, if any
+    verifier: Option<&'a dyn CertVerifier>,
+    /// The pins of the peer certificate, if any
+    pins: Option<&'a [CertificatePin]>,
+    /// Whether the pins replace the CA chain as the trust anchor
+    pins_only: bool,
+    /// Whether the verification failures need to be enforced by the callback rather than by MbedTLS
+    enforce: bool,
+    /// The verification failure flags of the peer certificate chain processed so far
+    flags: u32,
+    /// The error the handshake failed with because of the callback, if any
+    error: Option<SessionError>,
+}
+
+impl MInit for VerifyContext<'_> {}
+
+/// The MbedTLS certificate verification callback, checking the pins and delegating to the `CertVerifier` hook
+/// of the configuration
+///
+/// The certificates are processed from the root down to the peer's own certificate (depth 0).
+unsafe extern "C" fn verify_callback(
+    ctx: *mut c_void,
+    crt: *mut mbedtls_x509_crt,
+    depth: c_int,
+    flags: *mut u32,
+) -> c_int {
+    let verify = (ctx as *mut VerifyContext).as_mut().unwrap();
+    let flags = flags.as_mut().unwrap();
+    let cert = CertificateRef::new(&*crt);
+
+    if verify.pins_only {
+        // The chain is anchored by the pins rather than by a CA
+        *flags &= !MBEDTLS_X509_BADCERT_NOT_TRUSTED;
+    }
+
+    #[cfg(not(target_os = "espidf"))]
+    if crate::time::now().is_none() {
+        // The validity periods cannot be checked without knowing the current time
+        *flags &= !(MBEDTLS_X509_BADCERT_EXPIRED
+            | MBEDTLS_X509_BADCERT_FUTURE
+            | MBEDTLS_X509_BADCRL_EXPIRED
+            | MBEDTLS_X509_BADCRL_FUTURE);
+    }
+
+    if let Some(verifier) = verify.verifier {
+        let mut verifier_flags = VerifyFlag::from_bits(*flags);
+
+        verifier.verify(cert, depth as u32, &mut verifier_flags);
+
+        *flags = VerifyFlag::to_bits(verifier_flags);
+    }
+
+    verify.flags |= *flags;
+
+    if depth == 0 {
+        if let Some(pins) = verify.pins {
+            if !pins.iter().any(|pin| pin.matches(cert)) {
+                verify.error = Some(SessionError::PinMismatch);
+            }
+        }
+
+        if verify.error.is_none() && verify.enforce && verify.flags != 0 {
+            verify.error = Some(SessionError::MbedTls(MbedtlsError::new(
+                MBEDTLS_ERR_X509_CERT_VERIFY_FAILED,
+            )));
+        }
+
+        if verify.error.is_some() {
+            // Unlike `MBEDTLS_ERR_X509_CERT_VERIFY_FAILED`, a fatal error aborts the handshake
+            // even in optional verification mode
+            return MBEDTLS_ERR_X509_FATAL_ERROR;
+        }
+    }
+
+    0
+}
+
+/// The MbedTLS PSK callback, delegating to the `PskLookup` hook of the server configuration
+unsafe extern "C" fn psk_lookup_callback(
+    ctx: *mut c_void,
+    ssl: *mut mbedtls_ssl_context,
+    identity: *const c_uchar,
+    identity_len: usize,
+) -> c_int {
+    let psk_lookup = MCallback::<dyn PskLookup>::from_ptr(ctx);
+
+    match psk_lookup.lookup(core::slice::from_raw_parts(identity, identity_len)) {
+        Some(key) => mbedtls_ssl_set_hs_psk(ssl, key.as_ptr(), key.len()),
+        None => {
+            debug!("Unknown PSK identity");
+            MBEDTLS_ERR_SSL_UNKNOWN_IDENTITY
+        }
+    }
+}
+
+/// The maximum length of a server name (SNI), in bytes
+const MAX_SERVER_NAME_LEN: usize = 255;
+
+/// The server name (SNI) state of a server session
+///
+/// Meant to be allocated with `MBox` so that its address is stable for as long as MbedTLS keeps a pointer to it.
+struct SniContext<'a> {
+    /// The hook selecting the server credentials, if any
+    resolver: Option<&'a dyn SniResolver>,
+    /// The length of the server name requested by the client, 0 if none
+    len: usize,
+    /// The server name requested by the client
+    name: [u8; MAX_SERVER_NAME_LEN],
+}
+
+impl MInit for SniContext<'_> {}
+
+impl SniContext<'_> {
+    /// Get the server name requested by the client during the last handshake, if any
+    fn server_name(&self) -> Option<&str> {
+        (self.len > 0)
+            .then(|| core::str::from_utf8(&self.name[..self.len]).ok())
+            .flatten()
+    }
+}
+
+/// The MbedTLS SNI callback, capturing the server name and delegating to the `SniResolver` hook
+/// of the server configuration
+unsafe extern "C" fn sni_callback(
+    ctx: *mut c_void,
+    ssl: *mut mbedtls_ssl_context,
+    name: *const c_uchar,
+    name_len: usize,
+) -> c_int {
+    let sni = (ctx as *mut SniContext).as_mut().unwrap();
+    let name = core::slice::from_raw_parts(name, name_len);
+
+    let Some(name) = core::str::from_utf8(name)
+        .ok()
+        .filter(|name| name.len() <= MAX_SERVER_NAME_LEN)
+    else {
+        debug!("Invalid server name");
+        return -1;
+    };
+
+    sni.name[..name.len()].copy_from_slice(name.as_bytes());
+    sni.len = name.len();
+
+    let Some(resolver) = sni.resolver else {
+        return 0;
+    };
+
+    let Some(resolution) = resolver.resolve(name) else {
+        debug!("Unknown server name {}", name);
+        return -1;
+    };
+
+    let ret = mbedtls_ssl_set_hs_own_cert(
+        ssl,
+        &*resolution.creds.certificate.crt as *const _ as *mut _,
+        &*resolution.creds.private_key.0 as *const _ as *mut _,
+    );
+
+    if ret != 0 {
+        return ret;
+    }
+
+    if let Some(ca_chain) = resolution.ca_chain {
+        mbedtls_ssl_set_hs_ca_chain(
+            ssl,
+            &*ca_chain.crt as *const _ as *mut _,
+            resolution
+                .crl
+                .map(|crl| &*crl.crl as *const _ as *mut _)
+                .unwrap_or(core::ptr::null_mut()),
+        );
+    }
+
+    mbedtls_ssl_set_hs_authmode(ssl, resolution.auth_mode.mbedtls_authmode());
+
+    0
+}
+
+/// Get the ALPN protocol negotiated during the handshake, if any
+fn negotiated_alpn(ssl_context: &mbedtls_ssl_context) -> Option<&CStr> {
+    let protocol = unsafe { mbedtls_ssl_get_alpn_protocol(ssl_context) };
+
+    (!protocol.is_null()).then(|| unsafe { CStr::from_ptr(protocol) })
+}
+
+/// Get the result of the verification of the peer certificate chain
+fn verification_details(ssl_context: &mbedtls_ssl_context) -> EnumSet<VerifyFlag> {
+    VerifyFlag::from_bits(unsafe { mbedtls_ssl_get_verify_result(ssl_context) })
+}
+
+/// Get the certificate presented by the peer during the handshake, if any
+fn peer_certificate(ssl_context: &mbedtls_ssl_context) -> Option<CertificateRef<'_>> {
+    unsafe { mbedtls_ssl_get_peer_cert(ssl_context).as_ref() }.map(CertificateRef::new)
+}
+
+/// The status of the TLS 1.3 early data (0-RTT) offered by a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EarlyDataStatus {
+    /// No early data was offered to the server
+    ///
+    /// This is the case if early data is not enabled, if no session was resumed, or if the
+    /// ticket of the resumed session does not allow early data.
+    NotIndicated,
+    /// The early data was accepted by the server
+    Accepted,
+    /// The early data was rejected by the server and should be written again as normal data
+    Rejected,
+}
+
+/// Get the status of the early data offered by the client, once the handshake is complete
+///
+/// # Returns
+/// - `None` for server sessions, before the handshake is complete, or if MbedTLS is built without
+///   `MBEDTLS_SSL_EARLY_DATA`
+#[cfg(mbedtls_ssl_early_data)]
+fn early_data_status(ssl_context: &mbedtls_ssl_context) -> Option<EarlyDataStatus> {
+    // Does not modify the SSL context, despite taking a mutable pointer
+    let status = unsafe { mbedtls_ssl_get_early_data_status(ssl_context as *const _ as *mut _) };
+
+    #[allow(non_upper_case_globals)]
+    match u32::try_from(status).ok()? as mbedtls_ssl_early_data_status {
+        mbedtls_ssl_early_data_status_MBEDTLS_SSL_EARLY_DATA_STATUS_NOT_INDICATED => {
+            Some(EarlyDataStatus::NotIndicated)
+        }
+        mbedtls_ssl_early_data_status_MBEDTLS_SSL_EARLY_DATA_STATUS_ACCEPTED => {
+            Some(EarlyDataStatus::Accepted)
+        }
+        mbedtls_ssl_early_data_status_MBEDTLS_SSL_EARLY_DATA_STATUS_REJECTED => {
+            Some(EarlyDataStatus::Rejected)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(mbedtls_ssl_early_data))]
+fn early_data_status(_ssl_context: &mbedtls_ssl_context) -> Option<EarlyDataStatus> {
+    None
+}
+
+/// Write early data, progressing the handshake as necessary
+///
+/// # Returns
+/// - The result of `mbedtls_ssl_write_early_data`, or `MBEDTLS_ERR_SSL_CANNOT_WRITE_EARLY_DATA`
+///   if MbedTLS is built without `MBEDTLS_SSL_EARLY_DATA`
+///
+/// # Safety
+/// `ssl_context` must point to a valid, set up SSL context with BIO callbacks set
+unsafe fn write_early_data(ssl_context: *mut mbedtls_ssl_context, data: &[u8]) -> c_int {
+    #[cfg(mbedtls_ssl_early_data)]
+    let ret = mbedtls_ssl_write_early_data(ssl_context, data.as_ptr(), data.len());
+
+    #[cfg(not(mbedtls_ssl_early_data))]
+    let ret = {
+        let _ = (ssl_context, data);
+
+        MBEDTLS_ERR_SSL_CANNOT_WRITE_EARLY_DATA
+    };
+
+    ret
+}
+
+/// Read the early data received by the server during the handshake, if any is pending
+///
+/// # Returns
+/// - The number of bytes read, 0 if no early data is pending (always the case if MbedTLS is built
+///   without `MBEDTLS_SSL_EARLY_DATA`)
+fn read_early_data(
+    ssl_context: &mut mbedtls_ssl_context,
+    buf: &mut [u8],
+) -> Result<usize, SessionError> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+
+    #[cfg(mbedtls_ssl_early_data)]
+    let ret = unsafe { mbedtls_ssl_read_early_data(ssl_context, buf.as_mut_ptr(), buf.len()) };
+
+    #[cfg(not(mbedtls_ssl_early_data))]
+    let ret = {
+        let _ = ssl_context;
+
+        MBEDTLS_ERR_SSL_CANNOT_READ_EARLY_DATA
+    };
+
+    match ret {
+        MBEDTLS_ERR_SSL_CANNOT_READ_EARLY_DATA => Ok(0),
+        other => Ok(merr!(other)? as usize),
+    }
+}
+
+/// Discard the early data received by the server during the handshake, if any is pending
+fn discard_early_data(ssl_context: &mut mbedtls_ssl_context) {
+    let mut buf = [0; 64];
+    let mut discarded = 0;
+
+    while let Ok(len @ 1..) = read_early_data(ssl_context, &mut buf) {
+        discarded += len;
+    }
+
+    if discarded > 0 {
+        warn!(
+            "Discarded {}B of early data not read with `read_early_data`",
+            discarded
+        );
+    }
+}
+
+/// Export keying material from the session, as per RFC 5705 (TLS 1.2) or RFC 8446 section 7.5 (TLS 1.3)
+///
+/// Only available if MbedTLS is built with `MBEDTLS_SSL_KEYING_MATERIAL_EXPORT`.
+fn export_keying_material(
+    ssl_context: &mbedtls_ssl_context,
+    label: &str,
+    context: Option<&[u8]>,
+    out: &mut [u8],
+) -> Result<(), SessionError> {
+    #[cfg(not(mbedtls_ssl_keying_material_export))]
+    {
+        let _ = (ssl_context, label, context, out);
+
+        merr!(MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE)?;
+    }
+
+    #[cfg(mbedtls_ssl_keying_material_export)]
+    merr!(unsafe {
+        mbedtls_ssl_export_keying_material(
+            ssl_context as *const _ as *mut _,
+            out.as_mut_ptr(),
+            out.len(),
+            label.as_ptr() as *const _,
+            label.len(),
+            context.map(<[u8]>::as_ptr).unwrap_or(core::ptr::null()),
+            context.map(<[u8]>::len).unwrap_or(0),
+            context.is_some() as _,
+        )
+    })?;
+
+    Ok(())
+}
+
+/// The DTLS retransmission timer, as armed by MbedTLS via the `mbedtls_ssl_set_timer_cb` callbacks
+///
+/// Meant to be allocated with `MBox` so that its address is stable for as long as MbedTLS keeps a pointer to it.
+///
+/// As the timer callbacks cannot reach the `DtlsTimer` of the session, the current time is sampled
+/// with `SessionState::sample_time` before calling into MbedTLS.
+struct RetransmissionTimer {
+    /// The current time, as last sampled
+    now: Duration,
+    /// The time the timer was armed at
+    start: Duration,
+    /// The intermediate delay in milliseconds
+    int_ms: u32,
+    /// The final delay in milliseconds, or 0 if the timer is cancelled
+    fin_ms: u32,
+}
+
+impl MInit for RetransmissionTimer {}
+
+/// The MbedTLS callback arming (or cancelling) the DTLS retransmission timer
+unsafe extern "C" fn set_timer_callback(ctx: *mut c_void, int_ms: u32, fin_ms: u32) {
+    let timer = (ctx as *mut RetransmissionTimer).as_mut().unwrap();
+
+    timer.start = timer.now;
+    timer.int_ms = int_ms;
+    timer.fin_ms = fin_ms;
+}
+
+/// The MbedTLS callback reporting the status of the DTLS retransmission timer
+///
+/// # Returns
+/// - -1 if the timer is cancelled, 0 if no delay has passed, 1 if only the intermediate
+///   delay has passed, and 2 if the final delay has passed
+unsafe extern "C" fn get_timer_callback(ctx: *mut c_void) -> c_int {
+    let timer = (ctx as *const RetransmissionTimer).as_ref().unwrap();
+
+    if timer.fin_ms == 0 {
+        return -1;
+    }
+
+    let elapsed = timer.now.saturating_sub(timer.start);
+
+    if elapsed >= Duration::from_millis(timer.fin_ms as _) {
+        2
+    } else if elapsed >= Duration::from_millis(timer.int_ms as _) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Set a random connection ID of the provided length, for the peer to put in its records
+///
+/// Random, so that the peers of a `DtlsAcceptor` can be told apart by their connection IDs.
+#[cfg(mbedtls_ssl_dtls_connection_id)]
+fn set_random_cid(ssl_context: &mut mbedtls_ssl_context, len: usize) -> Result<(), SessionError> {
+    let mut own_cid = [0; MBEDTLS_SSL_CID_IN_LEN_MAX as usize];
+    let own_cid = &mut own_cid[..len];
+
+    merr!(unsafe { mbedtls_rng(core::ptr::null_mut(), own_cid.as_mut_ptr(), own_cid.len()) })?;
+
+    merr!(unsafe {
+        mbedtls_ssl_set_cid(
+            ssl_context,
+            MBEDTLS_SSL_CID_ENABLED as c_int,
+            own_cid.as_ptr(),
+            own_cid.len(),
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Get the connection ID the peer puts in its records, empty if none
+#[cfg(all(mbedtls_ssl_dtls_connection_id, feature = "edge-nal"))]
+fn own_cid(ssl_context: &mbedtls_ssl_context) -> &[u8] {
+    &ssl_context.private_own_cid[..ssl_context.private_own_cid_len as usize]
+}
+
+/// Set the address of the client as its transport-level identity, to which MbedTLS binds
+/// the DTLS HelloVerifyRequest cookies
+///
+/// No-op if MbedTLS is built without `MBEDTLS_SSL_DTLS_HELLO_VERIFY`, as there are no cookies then.
+///
+/// # Safety
+/// `ssl_context` must point to a valid, set up SSL context of a DTLS server session
+#[cfg(not(mbedtls_ssl_dtls_hello_verify))]
+unsafe fn set_client_transport_id(
+    _ssl_context: *mut mbedtls_ssl_context,
+    _addr: SocketAddr,
+) -> c_int {
+    0
+}
+
+/// Set the address of the client as its transport-level identity, to which MbedTLS binds
+/// the DTLS HelloVerifyRequest cookies
+///
+/// # Safety
+/// `ssl_context` must point to a valid, set up SSL context of a DTLS server session
+#[cfg(mbedtls_ssl_dtls_hello_verify)]
+unsafe fn set_client_transport_id(
+    ssl_context: *mut mbedtls_ssl_context,
+    addr: SocketAddr,
+) -> c_int {
+    // The IP address, followed by the port
+    let mut id = [0; 18];
+
+    let len = match addr.ip() {
+        IpAddr::V4(ip) => {
+            id[..4].copy_from_slice(&ip.octets());
+            4
+        }
+        IpAddr::V6(ip) => {
+            id[..16].copy_from_slice(&ip.octets());
+            16
+        }
+    };
+
+    id[len..len + 2].copy_from_slice(&addr.port().to_be_bytes());
+
+    mbedtls_ssl_set_client_transport_id(ssl_context, id.as_ptr(), len + 2)
+}
+
+/// Map an MbedTLS error to a `SessionError`, resolving `MBEDTLS_ERR_SSL_FATAL_ALERT_MESSAGE`
+/// to the fatal alert received from the peer
+fn session_error(ssl_context: &mbedtls_ssl_context, err: MbedtlsError) -> SessionError {
+    if err.code() == MBEDTLS_ERR_SSL_FATAL_ALERT_MESSAGE {
+        if let Some((level, description)) = received_alert(ssl_context) {
+            return SessionError::Alert { level, description };
+        }
+    }
+
+    SessionError::MbedTls(err)
+}
+
+/// Get the TLS version negotiated during the handshake
+fn negotiated_version(ssl_context: &mbedtls_ssl_context) -> TlsVersion {
+    // Before the handshake, this is the maximum configured version
+    TlsVersion::from_mbed_tls_version(ssl_context.private_tls_version).unwrap_or(TlsVersion::Tls1_2)
+}
+
+/// Get the ciphersuite negotiated during the handshake, if any
+fn negotiated_ciphersuite(ssl_context: &mbedtls_ssl_context) -> Option<Ciphersuite> {
+    let id = unsafe { mbedtls_ssl_get_ciphersuite_id_from_ssl(ssl_context) };
+
+    u16::try_from(id).ok().and_then(Ciphersuite::from_id)
+}
+
+/// The parameters of a handshake which are not retained by the SSL context once the handshake is over
+#[derive(Debug, Default, Clone, Copy)]
+struct HandshakeInfo {
+    /// The negotiated (EC)DHE group, if any
+    group: Option<NamedGroup>,
+    /// Whether the handshake went through the server certificate state,
+    /// i.e. whether it was a full (as opposed to an abbreviated) handshake
+    full: bool,
+}
+
+/// Perform the TLS handshake with the peer, capturing the negotiated (EC)DHE group along the way
+///
+/// This is equivalent to `mbedtls_ssl_handshake`, except that the handshake is driven step by step.
+/// MbedTLS does not provide an API for retrieving the negotiated group, and the handshake parameters
+/// holding it are freed once the handshake is over. Hence, the group is extracted from the handshake
+/// message which carries it according to the key exchange of the negotiated ciphersuite, i.e. the
+/// ServerHello (TLS 1.3) or the ServerKeyExchange (TLS 1.2 ECDHE), right after the step which
+/// received (client) or sent (server) it. No group is reported for the other key exchanges.
+///
+/// Similarly, whether the handshake is an abbreviated one (i.e. a session resumption) is tracked
+/// by checking whether the server certificate state is visited, as MbedTLS skips it
+/// when resuming a session.
+///
+/// # Arguments
+/// - `ssl_context` - The SSL context
+/// - `info` - Updated with the negotiated group, once it is known, and with the handshake type
+///
+/// # Returns
+/// - The result of the last `mbedtls_ssl_handshake_step` call, i.e. 0 when the handshake is over
+///
+/// # Safety
+/// `ssl_context` must point to a valid, set up SSL context with BIO callbacks set
+unsafe fn handshake(ssl_context: *mut mbedtls_ssl_context, info: &mut HandshakeInfo) -> c_int {
+    loop {
+        let state = (*ssl_context).private_state as mbedtls_ssl_states;
+
+        // Same as `mbedtls_ssl_is_handshake_over`, as the states past `MBEDTLS_SSL_HANDSHAKE_OVER`
+        // (e.g. the TLS 1.3 server sending its session tickets) are post-handshake ones
+        if state >= mbedtls_ssl_states_MBEDTLS_SSL_HANDSHAKE_OVER {
+            break 0;
+        }
+
+        if state == mbedtls_ssl_states_MBEDTLS_SSL_SERVER_CERTIFICATE {
+            info.full = true;
+        }
+
+        let ret = mbedtls_ssl_handshake_step(ssl_context);
+
+        #[allow(non_upper_case_globals)]
+        let group_msg = matches!(
+            state,
+            mbedtls_ssl_states_MBEDTLS_SSL_SERVER_HELLO
+                | mbedtls_ssl_states_MBEDTLS_SSL_HELLO_RETRY_REQUEST
+                | mbedtls_ssl_states_MBEDTLS_SSL_SERVER_KEY_EXCHANGE
+        );
+
+        if group_msg && info.group.is_none() {
+            info.group = handshake_group(&*ssl_context);
+        }
+
+        if ret != 0 {
+            break ret;
+        }
+    }
+}
+
+/// Extract the (EC)DHE group from the last handshake message received or sent, if any
+fn handshake_group(ssl_context: &mbedtls_ssl_context) -> Option<NamedGroup> {
+    let group_msg = unsafe { ssl_context.private_session_negotiate.as_ref() }
+        .and_then(|session| GroupMsg::from_ciphersuite(session.private_ciphersuite))?;
+
+    let dtls = unsafe { ssl_context.private_conf.as_ref() }
+        .is_some_and(|conf| conf.private_transport == MBEDTLS_SSL_TRANSPORT_DATAGRAM as u8);
+
+    let incoming = unsafe {
+        handshake_msg(
+            ssl_context.private_in_msgtype,
+            ssl_context.private_in_msg,
+            ssl_context.private_in_msglen,
+        )
+    };
+
+    let outgoing = unsafe {
+        handshake_msg(
+            ssl_context.private_out_msgtype,
+            ssl_context.private_out_msg,
+            ssl_context.private_out_msglen,
+        )
+    };
+
+    incoming
+        .into_iter()
+        .chain(outgoing)
+        .find_map(|msg| parse_handshake_group(msg, group_msg, dtls))
+        .and_then(NamedGroup::from_id)
+}
+
+/// The handshake message carrying the (EC)DHE group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupMsg {
+    /// The `key_share` extension of the TLS 1.3 ServerHello (or HelloRetryRequest)
+    ServerHello,
+    /// The ECDHE parameters of the TLS 1.2 ServerKeyExchange
+    ServerKeyExchange {
+        /// Whether the parameters are preceded by the PSK identity hint (ECDHE-PSK key exchange)
+        psk_hint: bool,
+    },
+}
+
+impl GroupMsg {
+    /// Get the message carrying the group for the key exchange of the ciphersuite with the provided ID
+    ///
+    /// # Returns
+    /// - `None` if the ciphersuite is unknown, or if its key exchange does not negotiate a named group
+    ///   (i.e. RSA, PSK, finite-field DHE and static ECDH key exchanges in TLS 1.2)
+    fn from_ciphersuite(id: c_int) -> Option<Self> {
+        let ciphersuite = unsafe { mbedtls_ssl_ciphersuite_from_id(id).as_ref() }?;
+
+        if ciphersuite.private_min_tls_version
+            == mbedtls_ssl_protocol_version_MBEDTLS_SSL_VERSION_TLS1_3 as u16
+        {
+            return Some(Self::ServerHello);
+        }
+
+        #[allow(non_upper_case_globals)]
+        match ciphersuite.private_key_exchange as mbedtls_key_exchange_type_t {
+            mbedtls_key_exchange_type_t_MBEDTLS_KEY_EXCHANGE_ECDHE_RSA
+            | mbedtls_key_exchange_type_t_MBEDTLS_KEY_EXCHANGE_ECDHE_ECDSA => {
+                Some(Self::ServerKeyExchange { psk_hint: false })
+            }
+            mbedtls_key_exchange_type_t_MBEDTLS_KEY_EXCHANGE_ECDHE_PSK => {
+                Some(Self::ServerKeyExchange { psk_hint: true })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Get the handshake message in the provided record buffer, if the record is a handshake one
+///
+/// # Safety
+/// `msg` must either be null or point to at least `len` bytes
+unsafe fn handshake_msg<'a>(msg_type: c_int, msg: *const u8, len: usize) -> Option<&'a [u8]> {
+    (msg_type == MBEDTLS_SSL_MSG_HANDSHAKE as c_int && !msg.is_null())
+        .then(|| core::slice::from_raw_parts(msg, len))
+}
+
+/// Parse the (EC)DHE group out of a handshake message, if it is the one carrying it
+///
+/// # Arguments
+/// - `msg` - The handshake message, including its header
+/// - `group_msg` - The handshake message carrying the group for the negotiated key exchange
+/// - `dtls` - Whether the message has a DTLS handshake header
+fn parse_handshake_group(msg: &[u8], group_msg: GroupMsg, dtls: bool) -> Option<u16> {
+    let mut msg = Reader(msg);
+
+    let msg_type = msg.u8()?;
+    let len = msg.bytes(3)?;
+
+    if dtls {
+        // message_seq, fragment_offset and fragment_length, making the DTLS header 12 bytes long
+        // rather than 4 (see `mbedtls_ssl_hs_hdr_len`). MbedTLS reassembles the fragmented messages
+        // before processing them, so the message is always complete.
+        msg.bytes(2 + 3 + 3)?;
+    }
+    let mut body = Reader(msg.bytes(u32::from_be_bytes([0, len[0], len[1], len[2]]) as usize)?);
+
+    match (msg_type as u32, group_msg) {
+        (MBEDTLS_SSL_HS_SERVER_HELLO, GroupMsg::ServerHello) => {
+            // legacy_version, random, legacy_session_id, cipher_suite, legacy_compression_method
+            body.bytes(2 + 32)?;
+            body.vec8()?;
+            body.bytes(2 + 1)?;
+
+            let mut extensions = Reader(body.vec16()?);
+            while !extensions.0.is_empty() {
+                let ext_type = extensions.u16()?;
+                let mut ext_data = Reader(extensions.vec16()?);
+
+                if ext_type == MBEDTLS_TLS_EXT_KEY_SHARE as u16 {
+                    // Either a `KeyShareEntry`, or - in a HelloRetryRequest - just the selected group
+                    return ext_data.u16();
+                }
+            }
+
+            None
+        }
+        (MBEDTLS_SSL_HS_SERVER_KEY_EXCHANGE, GroupMsg::ServerKeyExchange { psk_hint }) => {
+            if psk_hint {
+                body.vec16()?;
+            }
+
+            (body.u8()? == MBEDTLS_ECP_TLS_NAMED_CURVE as u8)
+                .then(|| body.u16())
+                .flatten()
+        }
+        _ => None,
+    }
+}
+
+/// A minimal big-endian reader over a TLS handshake message
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let (bytes, rest) = self.0.split_at_checked(len)?;
+        self.0 = rest;
+
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn vec8(&mut self) -> Option<&'a [u8]> {
+        let len = self.u8()?;
+        self.bytes(len as usize)
+    }
+
+    fn vec16(&mut self) -> Option<&'a [u8]> {
+        let len = self.u16()?;
+        self.bytes(len as usize)
+    }
+}
+
+/// The smallest record size limit allowed by RFC 8449
+const MIN_RECORD_SIZE_LIMIT: u16 = 64;
+
+/// Get the record size limit announced by the peer, if any
+#[cfg(mbedtls_ssl_record_size_limit)]
+fn peer_record_size_limit(ssl_context: &mbedtls_ssl_context) -> Option<u16> {
+    unsafe { ssl_context.private_session.as_ref() }
+        .map(|session| session.private_record_size_limit)
+        .filter(|limit| *limit >= MIN_RECORD_SIZE_LIMIT)
+}
+
+#[cfg(not(mbedtls_ssl_record_size_limit))]
+fn peer_record_size_limit(_ssl_context: &mbedtls_ssl_context) -> Option<u16> {
+    None
+}
+
+/// Cap a write length to the configured maximum write length, if any
+fn limit_write_len(len: usize, max_write_len: Option<u16>) -> usize {
+    max_write_len.map_or(len, |limit| len.min(limit as usize))
+}
+
+/// The maximum length of an ALPN protocol name, in bytes
+const MAX_ALPN_LEN: usize = 255;
+
+/// A snapshot of the parameters negotiated for a session
+///
+/// Returned by `Session::connection_info`.
+#[derive(Clone)]
+pub struct ConnectionInfo {
+    /// The negotiated TLS version
+    pub version: TlsVersion,
+    /// The negotiated ciphersuite, or `None` if the handshake is not complete yet
+    pub ciphersuite: Option<Ciphersuite>,
+    /// The negotiated (EC)DHE group, if any
+    pub group: Option<NamedGroup>,
+    /// Whether a previous session was resumed with an abbreviated handshake
+    ///
+    /// Note that TLS 1.3 handshakes authenticated with an external PSK are reported as resumed too,
+    /// as these do not involve certificates either.
+    pub resumed: bool,
+    /// The SHA-256 fingerprint of the peer certificate, or `None` if the peer did not present a certificate
+    pub peer_fingerprint: Option<[u8; 32]>,
+    /// The maximum number of bytes added to each record by the record protection
+    /// (header, MAC, padding, ...), or `None` if it is not known
+    pub record_expansion: Option<usize>,
+    /// The maximum size of the plaintext payload of an outgoing record, or `None` if it is not known
+    ///
+    /// Accounts for the negotiated maximum fragment length, the configured maximum write length and,
+    /// with TLS 1.3, the record size limit announced by the peer.
+    pub max_record_payload: Option<usize>,
+    /// The maximum size of the plaintext payload of an incoming record, or `None` if it is not known
+    ///
+    /// Accounts for the negotiated maximum fragment length.
+    pub max_in_record_payload: Option<usize>,
+    /// The maximum fragment length agreed with the peer (RFC 6066), or `None` if none was negotiated
+    pub max_fragment_len: Option<MaxFragmentLength>,
+    /// The record size limit announced by the peer (RFC 8449), or `None` if the peer did not announce one
+    ///
+    /// Only negotiated with TLS 1.3, and only if MbedTLS is built with `MBEDTLS_SSL_RECORD_SIZE_LIMIT`.
+    pub peer_record_size_limit: Option<u16>,
+    /// The length of the negotiated ALPN protocol, 0 if none
+    alpn_len: usize,
+    /// The negotiated ALPN protocol
+    alpn: [u8; MAX_ALPN_LEN],
+    /// The length of the server name, 0 if none
+    server_name_len: usize,
+    /// The server name (SNI)
+    server_name: [u8; MAX_SERVER_NAME_LEN],
+}
+
+impl ConnectionInfo {
+    /// Get the negotiated ALPN protocol
+    ///
+    /// # Returns
+    /// - The negotiated protocol, or `None` if ALPN was not configured or the peer did not agree on a protocol
+    pub fn alpn(&self) -> Option<&str> {
+        (self.alpn_len > 0)
+            .then(|| core::str::from_utf8(&self.alpn[..self.alpn_len]).ok())
+            .flatten()
+    }
+
+    /// Get the server name (SNI)
+    ///
+    /// # Returns
+    /// - For client sessions, the server name set with `set_server_name`, if any
+    /// - For server sessions, the server name requested by the client, if any
+    pub fn server_name(&self) -> Option<&str> {
+        (self.server_name_len > 0)
+            .then(|| core::str::from_utf8(&self.server_name[..self.server_name_len]).ok())
+            .flatten()
+    }
+}
+
+impl core::fmt::Debug for ConnectionInfo {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ConnectionInfo")
+            .field("version", &self.version)
+            .field("ciphersuite", &self.ciphersuite)
+            .field("group", &self.group)
+            .field("alpn", &self.alpn())
+            .field("server_name", &self.server_name())
+            .field("resumed", &self.resumed)
+            .field("peer_fingerprint", &self.peer_fingerprint)
+            .field("record_expansion", &self.record_expansion)
+            .field("max_record_payload", &self.max_record_payload)
+            .field("max_in_record_payload", &self.max_in_record_payload)
+            .field("max_fragment_len", &self.max_fragment_len)
+            .field("peer_record_size_limit", &self.peer_record_size_limit)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConnectionInfo {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "ConnectionInfo {{ version: {}, ciphersuite: {}, group: {}, alpn: {}, server_name: {}, resumed: {}, peer_fingerprint: {}, record_expansion: {}, max_record_payload: {}, max_in_record_payload: {}, max_fragment_len: {}, peer_record_size_limit: {} }}",
+            self.version,
+            self.ciphersuite,
+            self.group,
+            self.alpn(),
+            self.server_name(),
+            self.resumed,
+            self.peer_fingerprint,
+            self.record_expansion,
+            self.max_record_payload,
+            self.max_in_record_payload,
+            self.max_fragment_len,
+            self.peer_record_size_limit
+        )
+    }
+}
+
+/// Error type for session operations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// MBedTLS error
+    MbedTls(MbedtlsError),
+    /// IO error
+    Io(ErrorKind),
+    /// The configured minimum TLS version is higher than the maximum TLS version
+    InvalidVersionRange,
+    /// The peer certificate does not match any of the configured pins
+    PinMismatch,
+    /// The peer certificate is revoked by the configured CRL
+    CertificateRevoked,
+    /// The peer terminated the connection with a fatal alert
+    Alert {
+        /// The level of the alert
+        level: AlertLevel,
+        /// The description of the alert (e.g. `bad_certificate`)
+        description: AlertDescription,
+    },
+}
+
+impl SessionError {
+    /// Create a SessionError from an embedded-io Error
+    pub fn from_io<E: Error>(err: E) -> Self {
+        Self::Io(err.kind())
+    }
+}
+
+impl From<MbedtlsError> for SessionError {
+    fn from(e: MbedtlsError) -> Self {
+        Self::MbedTls(e)
+    }
+}
+
+impl From<ErrorKind> for SessionError {
+    fn from(e: ErrorKind) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl core::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::MbedTls(e) => write!(f, "{}", e),
+            Self::Io(e) => write!(f, "IO({:?})", e),
+            Self::InvalidVersionRange => write!(f, "InvalidVersionRange"),
+            Self::PinMismatch => write!(f, "PinMismatch"),
+            Self::CertificateRevoked => write!(f, "CertificateRevoked"),
+            Self::Alert { level, description } => write!(f, "Alert({:?}, {})", level, description),
+        }
+    }
+}
 
 #[cfg(feature = "defmt")]
 impl defmt::Format for SessionError {
@@ -310,6 +2294,12 @@ impl defmt::Format for SessionError {
         match self {
             Self::MbedTls(e) => defmt::write!(f, "{}", e),
             Self::Io(e) => defmt::write!(f, "IO({:?})", debug2format!(e)),
+            Self::InvalidVersionRange => defmt::write!(f, "InvalidVersionRange"),
+            Self::PinMismatch => defmt::write!(f, "PinMismatch"),
+            Self::CertificateRevoked => defmt::write!(f, "CertificateRevoked"),
+            Self::Alert { level, description } => {
+                defmt::write!(f, "Alert({}, {=str})", level, description.name())
+            }
         }
     }
 }
@@ -324,3 +2314,228 @@ impl embedded_io::Error for SessionError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_handshake_group, GroupMsg};
+
+    const X25519: u16 = 0x001d;
+    const SECP256R1: u16 = 0x0017;
+
+    /// A minimal writer of TLS handshake messages
+    struct Writer {
+        buf: [u8; 512],
+        len: usize,
+    }
+
+    impl Writer {
+        fn new() -> Self {
+            Self {
+                buf: [0; 512],
+                len: 0,
+            }
+        }
+
+        fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+            self.buf[self.len..self.len + bytes.len()].copy_from_slice(bytes);
+            self.len += bytes.len();
+            self
+        }
+
+        fn u8(&mut self, value: u8) -> &mut Self {
+            self.bytes(&[value])
+        }
+
+        fn u16(&mut self, value: u16) -> &mut Self {
+            self.bytes(&value.to_be_bytes())
+        }
+
+        fn u24(&mut self, value: usize) -> &mut Self {
+            self.bytes(&(value as u32).to_be_bytes()[1..])
+        }
+
+        fn vec8(&mut self, bytes: &[u8]) -> &mut Self {
+            self.u8(bytes.len() as u8).bytes(bytes)
+        }
+
+        fn vec16(&mut self, bytes: &[u8]) -> &mut Self {
+            self.u16(bytes.len() as u16).bytes(bytes)
+        }
+
+        fn as_slice(&self) -> &[u8] {
+            &self.buf[..self.len]
+        }
+    }
+
+    /// Frame a handshake message body with a TLS or DTLS handshake header
+    fn message(msg_type: u8, body: &[u8], dtls: bool) -> Writer {
+        let mut msg = Writer::new();
+
+        msg.u8(msg_type).u24(body.len());
+
+        if dtls {
+            // message_seq, fragment_offset, fragment_length
+            msg.u16(1).u24(0).u24(body.len());
+        }
+
+        msg.bytes(body);
+
+        msg
+    }
+
+    /// A TLS 1.3 ServerHello with the provided `key_share` extension data
+    fn server_hello(key_share: &[u8]) -> Writer {
+        let mut extensions = Writer::new();
+        // supported_versions: TLS 1.3
+        extensions.u16(43).vec16(&[0x03, 0x04]);
+        extensions.u16(51).vec16(key_share);
+
+        let mut body = Writer::new();
+        body.u16(0x0303)
+            .bytes(&[0xaa; 32])
+            .vec8(&[0xbb; 32])
+            .u16(0x1301)
+            .u8(0)
+            .vec16(extensions.as_slice());
+
+        message(2, body.as_slice(), false)
+    }
+
+    /// A TLS 1.2 ECDHE ServerKeyExchange for the provided group, optionally preceded by a PSK identity hint
+    fn server_key_exchange(group: u16, psk_hint: Option<&[u8]>, dtls: bool) -> Writer {
+        let mut body = Writer::new();
+
+        if let Some(psk_hint) = psk_hint {
+            body.vec16(psk_hint);
+        }
+
+        // named_curve, the group, the public point, and a signature
+        body.u8(3).u16(group).vec8(&[0x04; 65]);
+        if psk_hint.is_none() {
+            body.u16(0x0403).vec16(&[0xcc; 70]);
+        }
+
+        message(12, body.as_slice(), dtls)
+    }
+
+    #[test]
+    fn tls13_server_hello() {
+        let mut key_share = Writer::new();
+        key_share.u16(X25519).vec16(&[0xdd; 32]);
+
+        let msg = server_hello(key_share.as_slice());
+
+        assert_eq!(
+            parse_handshake_group(msg.as_slice(), GroupMsg::ServerHello, false),
+            Some(X25519)
+        );
+    }
+
+    #[test]
+    fn tls13_hello_retry_request() {
+        // Only the selected group, without a key exchange
+        let msg = server_hello(&SECP256R1.to_be_bytes());
+
+        assert_eq!(
+            parse_handshake_group(msg.as_slice(), GroupMsg::ServerHello, false),
+            Some(SECP256R1)
+        );
+    }
+
+    #[test]
+    fn tls12_server_key_exchange() {
+        let msg = server_key_exchange(SECP256R1, None, false);
+
+        assert_eq!(
+            parse_handshake_group(
+                msg.as_slice(),
+                GroupMsg::ServerKeyExchange { psk_hint: false },
+                false
+            ),
+            Some(SECP256R1)
+        );
+    }
+
+    #[test]
+    fn tls12_server_key_exchange_psk_hint() {
+        for psk_hint in [&b""[..], b"hint"] {
+            let msg = server_key_exchange(X25519, Some(psk_hint), false);
+
+            assert_eq!(
+                parse_handshake_group(
+                    msg.as_slice(),
+                    GroupMsg::ServerKeyExchange { psk_hint: true },
+                    false
+                ),
+                Some(X25519)
+            );
+        }
+    }
+
+    #[test]
+    fn dtls_server_key_exchange() {
+        let msg = server_key_exchange(SECP256R1, None, true);
+
+        assert_eq!(
+            parse_handshake_group(
+                msg.as_slice(),
+                GroupMsg::ServerKeyExchange { psk_hint: false },
+                true
+            ),
+            Some(SECP256R1)
+        );
+    }
+
+    #[test]
+    fn other_message() {
+        let msg = server_key_exchange(SECP256R1, None, false);
+
+        assert_eq!(
+            parse_handshake_group(msg.as_slice(), GroupMsg::ServerHello, false),
+            None
+        );
+
+        // Not a named curve
+        let mut body = Writer::new();
+        body.u8(1).u16(SECP256R1);
+
+        let msg = message(12, body.as_slice(), false);
+
+        assert_eq!(
+            parse_handshake_group(
+                msg.as_slice(),
+                GroupMsg::ServerKeyExchange { psk_hint: false },
+                false
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let mut key_share = Writer::new();
+        key_share.u16(X25519).vec16(&[0xdd; 32]);
+
+        let server_hello = server_hello(key_share.as_slice());
+        let server_key_exchange = server_key_exchange(SECP256R1, None, true);
+
+        for (msg, group_msg, dtls) in [
+            (&server_hello, GroupMsg::ServerHello, false),
+            (
+                &server_key_exchange,
+                GroupMsg::ServerKeyExchange { psk_hint: false },
+                true,
+            ),
+        ] {
+            let msg = msg.as_slice();
+
+            for len in 0..msg.len() {
+                assert_eq!(
+                    parse_handshake_group(&msg[..len], group_msg, dtls),
+                    None,
+                    "Truncated to {len} bytes"
+                );
+            }
+        }
+    }
+}
@@ -5,12 +5,28 @@ use core::task::{Context, Poll};
 
 use embedded_io::ErrorKind;
 
+use enumset::EnumSet;
+
 use io::{ErrorType, Read, Write};
 
 use crate::sys::*;
-use crate::{SessionError, TlsReference};
+use crate::{
+    AlertDescription, AlertLevel, CertificateRef, Ciphersuite, EarlyDataStatus, NamedGroup,
+    SessionError, TlsReference, TlsVersion, VerifyFlag,
+};
+
+use super::{
+    discard_early_data, early_data_status, export_keying_material, handshake, limit_write_len,
+    negotiated_alpn, negotiated_ciphersuite, negotiated_version, peer_certificate, read_early_data,
+    session_error, verification_details, write_early_data, ConnectionInfo, HandshakeInfo,
+    SessionConfig, SessionState,
+};
+
+#[cfg(feature = "edge-nal")]
+pub use dtls::*;
 
-use super::{SessionConfig, SessionState};
+#[cfg(feature = "edge-nal")]
+mod dtls;
 
 /// Re-export of the `embedded-io-async` crate so that users don't have to explicitly depend on it
 /// to use e.g. `write_all` or `read_exact`.
@@ -66,7 +82,7 @@ where
     ) -> Result<Self, SessionError> {
         Ok(Self {
             stream,
-            state: SessionState::new(config)?,
+            state: SessionState::new(config, false)?,
             connected: false,
             eof: false,
             read_byte: None,
@@ -77,15 +93,152 @@ where
 
     /// Get the TLS verification details
     ///
-    /// The details are a bitmask of various flags indicating the result of the certificate verification.
+    /// The details are a set of flags indicating the result of the certificate verification.
+    ///
+    /// # Returns
+    /// - An empty set if verification succeeded
+    /// - The verification failure flags otherwise (all flags if no verification result is available,
+    ///   e.g. because the handshake was aborted early)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn tls_verification_details(&self) -> EnumSet<VerifyFlag> {
+        verification_details(&self.state.ssl_context)
+    }
+
+    /// Get the certificate presented by the peer during the handshake
+    ///
+    /// The returned certificate is the first one of the peer certificate chain; the rest of the chain
+    /// is linked from it.
+    ///
+    /// # Returns
+    /// - The peer certificate, or `None` if the peer did not present a certificate
+    ///   (e.g. a client when client authentication is not requested)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn peer_certificate(&self) -> Option<CertificateRef<'_>> {
+        peer_certificate(&self.state.ssl_context)
+    }
+
+    /// Get the server name (SNI) requested by the client
+    ///
+    /// Server sessions only.
     ///
     /// # Returns
-    /// - 0 if verification succeeded
-    /// - A bitmask of verification failure flags otherwise
+    /// - The server name, or `None` if the client did not request a server name or this is a client session
     ///
     /// NOTE: This function should be called only after a `connect()` call.
-    pub fn tls_verification_details(&self) -> u32 {
-        unsafe { mbedtls_ssl_get_verify_result(&*self.state.ssl_context) }
+    pub fn server_name(&self) -> Option<&str> {
+        self.state.sni.as_ref().and_then(|sni| sni.server_name())
+    }
+
+    /// Get the ALPN protocol negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated protocol, or `None` if ALPN was not configured or the peer did not agree on a protocol
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_alpn(&self) -> Option<&CStr> {
+        negotiated_alpn(&self.state.ssl_context)
+    }
+
+    /// Get the TLS version negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_version(&self) -> TlsVersion {
+        negotiated_version(&self.state.ssl_context)
+    }
+
+    /// Get the ciphersuite negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated ciphersuite, or `None` if the handshake is not complete yet
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn ciphersuite(&self) -> Option<Ciphersuite> {
+        negotiated_ciphersuite(&self.state.ssl_context)
+    }
+
+    /// Get the (EC)DHE group negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated group, or `None` if the handshake is not complete yet or did not involve
+    ///   an ephemeral (EC)DHE key exchange with a named group (e.g. RSA or plain PSK key exchange,
+    ///   TLS 1.2 finite-field DHE, or an abbreviated TLS 1.2 resumption handshake)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn group(&self) -> Option<NamedGroup> {
+        self.state.handshake.group
+    }
+
+    /// Export keying material bound to the session, e.g. for channel binding
+    ///
+    /// The keying material is derived as per RFC 5705 for TLS 1.2, and as per RFC 8446 section 7.5
+    /// for TLS 1.3, so that both peers obtain the same value for the same label and context.
+    ///
+    /// # Arguments
+    /// - `label` - The label identifying the usage of the keying material (e.g. `EXPORTER-my-protocol`)
+    /// - `context` - The optional context value; for TLS 1.2, no context and an empty context
+    ///   result in different keying material
+    /// - `out` - The buffer to fill with the keying material
+    ///
+    /// # Returns
+    /// - An error if the handshake is not complete yet (`MBEDTLS_ERR_SSL_BAD_INPUT_DATA`),
+    ///   if `out` is larger than what MbedTLS supports, or if MbedTLS is built without
+    ///   `MBEDTLS_SSL_KEYING_MATERIAL_EXPORT` (`MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE`)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), SessionError> {
+        export_keying_material(&self.state.ssl_context, label, context, out)
+    }
+
+    /// Get a snapshot of the parameters negotiated with the peer
+    ///
+    /// Meant for logging and telemetry; all the information is also available via the
+    /// individual accessors of the session.
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.state.connection_info()
+    }
+
+    /// Save the session negotiated with the server, so that it can be resumed later
+    /// by a new `Session` instance, with an abbreviated handshake
+    ///
+    /// The saved session can be persisted (e.g. in RTC memory or flash) and restored
+    /// with `load_session`. Both TLS 1.2 sessions (session IDs and tickets) and TLS 1.3
+    /// session tickets are supported. Note that TLS 1.3 servers send their session tickets
+    /// after the handshake, so these are only received once data is read from the session.
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to serialize the session into
+    ///
+    /// # Returns
+    /// - The length of the serialized session, or an error (`MBEDTLS_ERR_SSL_BUFFER_TOO_SMALL` if `buf` is too small)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn save_session(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.state.save_session(buf)
+    }
+
+    /// Load a session previously saved with `save_session`, so that it is resumed by the handshake
+    ///
+    /// If the server does not agree to resume the session, a full handshake is performed instead.
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `data` - The serialized session
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn load_session(&mut self, data: &[u8]) -> Result<(), SessionError> {
+        self.state.load_session(data)
     }
 
     /// Get a mutable reference to the underlying stream
@@ -116,14 +269,138 @@ where
             return Ok(());
         }
 
-        MBio::from_session(self).connect().await?;
-
-        self.connected = true;
-        self.eof = false;
+        self.handshake(false).await?;
 
         Ok(())
     }
 
+    /// Write TLS 1.3 early data (0-RTT), i.e. data sent to the server along with the first
+    /// handshake messages, before the handshake is complete
+    ///
+    /// Client sessions with `early_data` enabled only. The handshake is started if necessary,
+    /// and completed by a subsequent `connect()` call (or by the first read or write operation).
+    ///
+    /// Once the handshake is complete, `early_data_status` tells whether the server accepted the
+    /// early data. If it did not, the data has to be written again with `write()`.
+    ///
+    /// Note that early data is not protected against replays, so it should only carry idempotent requests.
+    ///
+    /// # Arguments
+    /// - `data` - The data to write
+    ///
+    /// # Returns
+    /// - The number of bytes written, 0 if early data cannot be written (e.g. because no session was loaded
+    ///   with `load_session`, because its ticket does not allow early data, because the maximum
+    ///   amount of early data accepted by the server is reached, or because the handshake is complete)
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub async fn write_early_data(&mut self, data: &[u8]) -> Result<usize, SessionError> {
+        if self.connected {
+            return Ok(0);
+        }
+
+        if !self.state.in_handshake {
+            self.state.reset()?;
+            self.state.in_handshake = true;
+        }
+
+        let data = self.state.limit_write(data);
+
+        let result = MBio::from_session(self).write_early_data(data).await;
+
+        result.map_err(|e| {
+            self.state.in_handshake = false;
+            self.state.handshake_error(e)
+        })
+    }
+
+    /// Read TLS 1.3 early data (0-RTT) sent by the client
+    ///
+    /// Server sessions with `max_early_data_size` set only. The handshake is performed until
+    /// early data is received from the client, or until the handshake is complete.
+    /// This function should thus be called repeatedly until it returns 0, at which point
+    /// the handshake is complete. Early data not read with this function is discarded.
+    ///
+    /// Note that early data is not protected against replays, so it should only carry idempotent requests.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to read the early data into
+    ///
+    /// # Returns
+    /// - The number of bytes read, 0 if all early data (if any) was read and the handshake is complete
+    ///
+    /// NOTE: This function should be called instead of - or before - the `connect()` call.
+    pub async fn read_early_data(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        while !self.connected {
+            if self.state.in_handshake {
+                let len = read_early_data(&mut self.state.ssl_context, buf)?;
+                if len > 0 {
+                    return Ok(len);
+                }
+            }
+
+            self.handshake(true).await?;
+        }
+
+        Ok(0)
+    }
+
+    /// Get the status of the TLS 1.3 early data (0-RTT) written with `write_early_data`
+    ///
+    /// Client sessions only.
+    ///
+    /// # Returns
+    /// - The early data status, or `None` if this is a server session, if the handshake is not complete,
+    ///   or if MbedTLS is built without `MBEDTLS_SSL_EARLY_DATA`
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn early_data_status(&self) -> Option<EarlyDataStatus> {
+        early_data_status(&self.state.ssl_context)
+    }
+
+    /// Perform - or continue - the TLS handshake
+    ///
+    /// # Arguments
+    /// - `early_data` - Whether to interrupt the handshake when early data is received from the client,
+    ///   rather than discarding it
+    ///
+    /// # Returns
+    /// - `true` if the handshake was interrupted because early data was received, `false` if it is complete
+    async fn handshake(&mut self, early_data: bool) -> Result<bool, SessionError> {
+        if !self.state.in_handshake {
+            self.state.reset()?;
+            self.state.in_handshake = true;
+        } else if !early_data {
+            discard_early_data(&mut self.state.ssl_context);
+        }
+
+        let mut info = self.state.handshake;
+        let result = loop {
+            match MBio::from_session(self).connect(&mut info).await {
+                Ok(true) if !early_data => discard_early_data(&mut self.state.ssl_context),
+                result => break result,
+            }
+        };
+
+        self.state.handshake = info;
+
+        match result {
+            Ok(true) => Ok(true),
+            Ok(false) => {
+                self.state.in_handshake = false;
+                self.connected = true;
+                self.eof = false;
+
+                Ok(false)
+            }
+            Err(e) => {
+                self.state.in_handshake = false;
+
+                Err(self.state.handshake_error(e))
+            }
+        }
+    }
+
     /// Split the TLS session into read and write halves
     ///
     /// # Returns
@@ -158,6 +435,7 @@ where
                 eof: false,
                 read_byte: None,
                 write_byte: &mut self.write_byte,
+                max_write_len: self.state.max_write_len,
             },
         ))
     }
@@ -193,6 +471,8 @@ where
             return Ok(0);
         }
 
+        let data = self.state.limit_write(data);
+
         MBio::from_session(self).write(data).await
     }
 
@@ -225,6 +505,30 @@ where
 
         Ok(())
     }
+
+    /// Send an alert to the peer
+    ///
+    /// Useful for rejecting the peer with a specific alert (e.g. `access_denied`) before closing
+    /// the connection. After sending a fatal alert, the session is considered closed.
+    ///
+    /// # Arguments
+    /// - `level` - The level of the alert
+    /// - `description` - The description of the alert
+    pub async fn send_alert(
+        &mut self,
+        level: AlertLevel,
+        description: AlertDescription,
+    ) -> Result<(), SessionError> {
+        MBio::from_session(self)
+            .send_alert(level, description)
+            .await?;
+
+        if level == AlertLevel::Fatal {
+            self.connected = false;
+        }
+
+        Ok(())
+    }
 }
 
 impl<T> Drop for Session<'_, T>
@@ -330,6 +634,23 @@ impl<T> SessionRead<'_, T>
 where
     T: Read,
 {
+    /// Get the TLS verification details
+    ///
+    /// # Returns
+    /// - An empty set if verification succeeded
+    /// - The verification failure flags otherwise
+    pub fn tls_verification_details(&self) -> EnumSet<VerifyFlag> {
+        verification_details(self.ssl_context)
+    }
+
+    /// Get the certificate presented by the peer during the handshake
+    ///
+    /// # Returns
+    /// - The peer certificate, or `None` if the peer did not present a certificate
+    pub fn peer_certificate(&self) -> Option<CertificateRef<'_>> {
+        peer_certificate(self.ssl_context)
+    }
+
     pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
         if *self.eof || buf.is_empty() {
             return Ok(0);
@@ -371,6 +692,8 @@ where
     read_byte: Option<u8>,
     /// A state necessary so as to implement `MBio::wait_writable`
     write_byte: &'a mut Option<u8>,
+    /// The configured maximum length of the plaintext of the records sent to the peer, if any
+    max_write_len: Option<u16>,
 }
 
 impl<T> SessionWrite<'_, T>
@@ -389,6 +712,8 @@ where
             return Ok(0);
         }
 
+        let data = &data[..limit_write_len(data.len(), self.max_write_len)];
+
         MBio::from_write(self).write(data).await
     }
 
@@ -523,15 +848,50 @@ where
     }
 
     /// Establish the SSL connection
-    async fn connect(&mut self) -> Result<(), SessionError> {
+    ///
+    /// # Arguments
+    /// - `info` - Updated with the parameters of the handshake not retained by the SSL context
+    ///
+    /// # Returns
+    /// - `true` if the handshake was interrupted because early data was received, `false` if it is complete
+    async fn connect(&mut self, info: &mut HandshakeInfo) -> Result<bool, SessionError> {
         debug!("Establishing SSL connection");
 
-        merr!(unsafe { mbedtls_ssl_session_reset(self.ssl_context as *const _ as *mut _) })?;
+        loop {
+            match self
+                .call_mbedtls(|ssl_ctx| unsafe { handshake(ssl_ctx as *const _ as *mut _, info) })
+                .await
+            {
+                MBEDTLS_ERR_SSL_WANT_READ => {
+                    if !self.wait_readable().await.map_err(SessionError::from_io)? {
+                        return Err(SessionError::Io(ErrorKind::BrokenPipe));
+                    }
+                }
+                MBEDTLS_ERR_SSL_WANT_WRITE => {
+                    if !self.wait_writable().await.map_err(SessionError::from_io)? {
+                        return Err(SessionError::Io(ErrorKind::BrokenPipe));
+                    }
+                }
+                // See https://github.com/Mbed-TLS/mbedtls/issues/8749
+                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
+                MBEDTLS_ERR_SSL_RECEIVED_EARLY_DATA => break Ok(true),
+                other => {
+                    merr!(other).map_err(|e| session_error(self.ssl_context, e))?;
+                    break Ok(false);
+                }
+            }
+        }
+    }
 
+    /// Write early data, progressing the handshake as necessary
+    ///
+    /// # Returns
+    /// - The number of bytes written, 0 if early data cannot be written
+    async fn write_early_data(&mut self, data: &[u8]) -> Result<usize, SessionError> {
         loop {
             match self
                 .call_mbedtls(|ssl_ctx| unsafe {
-                    mbedtls_ssl_handshake(ssl_ctx as *const _ as *mut _)
+                    write_early_data(ssl_ctx as *const _ as *mut _, data)
                 })
                 .await
             {
@@ -545,11 +905,10 @@ where
                         return Err(SessionError::Io(ErrorKind::BrokenPipe));
                     }
                 }
-                // See https://github.com/Mbed-TLS/mbedtls/issues/8749
-                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
+                MBEDTLS_ERR_SSL_CANNOT_WRITE_EARLY_DATA => break Ok(0),
                 other => {
-                    merr!(other)?;
-                    break Ok(());
+                    let len = merr!(other).map_err(|e| session_error(self.ssl_context, e))?;
+                    break Ok(len as usize);
                 }
             }
         }
@@ -580,13 +939,17 @@ where
                     }
                 }
                 // See https://github.com/Mbed-TLS/mbedtls/issues/8749
-                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
+                // The ticket is retained in the SSL context and can be exported with `save_session`
+                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => {
+                    debug!("Received a new TLS 1.3 session ticket");
+                    continue;
+                }
                 MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY => {
                     *self.eof = true;
                     break Ok(0);
                 }
                 other => {
-                    let len = merr!(other)?;
+                    let len = merr!(other).map_err(|e| session_error(self.ssl_context, e))?;
                     break Ok(len as usize);
                 }
             }
@@ -620,7 +983,7 @@ where
                 // See https://github.com/Mbed-TLS/mbedtls/issues/8749
                 MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
                 other => {
-                    let len = merr!(other)?;
+                    let len = merr!(other).map_err(|e| session_error(self.ssl_context, e))?;
                     break Ok(len as usize);
                 }
             }
@@ -649,6 +1012,38 @@ where
         Ok(())
     }
 
+    /// Send an alert to the peer and flush the stream
+    async fn send_alert(
+        &mut self,
+        level: AlertLevel,
+        description: AlertDescription,
+    ) -> Result<(), SessionError> {
+        loop {
+            match self
+                .call_mbedtls(|ssl| unsafe {
+                    mbedtls_ssl_send_alert_message(
+                        ssl as *const _ as *mut _,
+                        level.id(),
+                        description.id(),
+                    )
+                })
+                .await
+            {
+                MBEDTLS_ERR_SSL_WANT_WRITE => {
+                    if !self.wait_writable().await.map_err(SessionError::from_io)? {
+                        return Err(SessionError::Io(ErrorKind::BrokenPipe));
+                    }
+                }
+                other => {
+                    merr!(other)?;
+                    break;
+                }
+            }
+        }
+
+        self.flush().await
+    }
+
     /// Wait until the underlying stream is readable
     ///
     /// A side effect of this function is that it reads one byte from the stream
@@ -0,0 +1,1281 @@
+use core::ffi::{c_int, c_uchar, c_void};
+use core::future::{poll_fn, Future};
+use core::net::SocketAddr;
+use core::pin::pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+
+use edge_nal::{UdpReceive, UdpSend};
+
+use embedded_io::ErrorKind;
+
+use embedded_io_async::ErrorType;
+
+use enumset::EnumSet;
+
+use crate::sys::*;
+use crate::{
+    AlertDescription, AlertLevel, CertificateRef, Ciphersuite, DtlsCookies, ServerSessionConfig,
+    SessionError, TlsReference, TlsVersion, VerifyFlag,
+};
+
+use super::super::{
+    export_keying_material, handshake, negotiated_ciphersuite, negotiated_version,
+    peer_certificate, session_error, set_client_transport_id, verification_details, ConnectionInfo,
+    HandshakeInfo, SessionConfig, SessionState,
+};
+#[cfg(mbedtls_ssl_dtls_connection_id)]
+use super::super::{own_cid, set_random_cid};
+
+/// A monotonic timer driving the retransmissions of the DTLS handshake messages
+#[allow(async_fn_in_trait)]
+pub trait DtlsTimer {
+    /// Get the current time
+    ///
+    /// # Returns
+    /// - The time elapsed since an arbitrary (but fixed) point in the past, e.g. since boot
+    fn now(&self) -> Duration;
+
+    /// Wait until the provided time is reached
+    ///
+    /// The returned future is polled until it completes, or until it is no longer needed
+    /// (e.g. because a datagram was received, or because the deadline changed).
+    ///
+    /// # Arguments
+    /// - `deadline` - The time to wait for, on the same scale as `now`
+    async fn wait_until(&self, deadline: Duration);
+}
+
+impl<T> DtlsTimer for &T
+where
+    T: DtlsTimer,
+{
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    async fn wait_until(&self, deadline: Duration) {
+        (**self).wait_until(deadline).await
+    }
+}
+
+/// An async DTLS session over a datagram socket represented by `edge-nal`'s `UdpSend` and `UdpReceive` traits.
+///
+/// The session is bound to a single peer. Datagrams received from other addresses are discarded.
+/// For serving multiple clients over a single socket, use `DtlsAcceptor`.
+///
+/// Unlike with TLS, the data of each `write` call is sent in a single record (and datagram),
+/// and each `read` call returns the data of at most one record.
+pub struct DtlsSession<'a, S, T>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    /// The underlying datagram socket
+    socket: S,
+    /// The address of the peer
+    remote: Option<SocketAddr>,
+    /// The timer driving the retransmissions
+    timer: T,
+    /// The session state
+    state: SessionState<'a>,
+    /// Whether the session is connected
+    connected: bool,
+    /// Whether we received a close notify from the peer
+    eof: bool,
+    /// Reference to the active Tls instance
+    _token: TlsReference<'a>,
+}
+
+impl<'a, S, T> DtlsSession<'a, S, T>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    /// Create a session for a DTLS connection.
+    ///
+    /// # Arguments
+    /// - `tls` - A reference to the active `Tls` instance.
+    /// - `socket` - The datagram socket for the connection, implementing `UdpSend` and `UdpReceive`.
+    /// - `remote` - The address of the peer. Mandatory for client sessions. For server sessions, `None`
+    ///   binds the session to the address of the first datagram received.
+    /// - `timer` - The timer driving the retransmissions of the handshake messages.
+    /// - `mtu` - The maximum size of the datagrams sent to the peer (i.e. the path MTU, minus the size
+    ///   of the IP and UDP headers), or 0 for no limit. The handshake messages are fragmented accordingly,
+    ///   and the application data written in a single call must fit in one datagram.
+    /// - `config` - The session configuration. DTLS only supports TLS 1.2 (i.e. DTLS 1.2), so `max_version`
+    ///   is capped accordingly.
+    ///
+    /// # Returns
+    /// - A `DtlsSession` instance or an error on failure.
+    pub fn new(
+        tls: TlsReference<'a>,
+        socket: S,
+        remote: Option<SocketAddr>,
+        timer: T,
+        mtu: u16,
+        config: &SessionConfig<'a>,
+    ) -> Result<Self, SessionError> {
+        let mut state = SessionState::new(config, true)?;
+
+        unsafe {
+            mbedtls_ssl_set_mtu(&mut *state.ssl_context, mtu);
+        }
+
+        Ok(Self {
+            socket,
+            remote,
+            timer,
+            state,
+            connected: false,
+            eof: false,
+            _token: tls,
+        })
+    }
+
+    /// Get a mutable reference to the underlying socket
+    pub fn socket(&mut self) -> &mut S {
+        &mut self.socket
+    }
+
+    /// Get the address of the peer
+    ///
+    /// # Returns
+    /// - The address of the peer, or `None` for a server session which did not receive any datagram yet
+    pub fn remote(&self) -> Option<SocketAddr> {
+        self.remote
+    }
+
+    /// Get the DTLS verification details
+    ///
+    /// The details are a set of flags indicating the result of the certificate verification.
+    ///
+    /// # Returns
+    /// - An empty set if verification succeeded
+    /// - The verification failure flags otherwise (all flags if no verification result is available,
+    ///   e.g. because the handshake was aborted early)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn tls_verification_details(&self) -> EnumSet<VerifyFlag> {
+        verification_details(&self.state.ssl_context)
+    }
+
+    /// Get the certificate presented by the peer during the handshake
+    ///
+    /// # Returns
+    /// - The peer certificate, or `None` if the peer did not present a certificate
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn peer_certificate(&self) -> Option<CertificateRef<'_>> {
+        peer_certificate(&self.state.ssl_context)
+    }
+
+    /// Get the TLS version negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_version(&self) -> TlsVersion {
+        negotiated_version(&self.state.ssl_context)
+    }
+
+    /// Get the ciphersuite negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated ciphersuite, or `None` if the handshake is not complete yet
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn ciphersuite(&self) -> Option<Ciphersuite> {
+        negotiated_ciphersuite(&self.state.ssl_context)
+    }
+
+    /// Export keying material bound to the session, as per RFC 5705
+    ///
+    /// # Arguments
+    /// - `label` - The label identifying the usage of the keying material
+    /// - `context` - The optional context value; no context and an empty context result in different keying material
+    /// - `out` - The buffer to fill with the keying material
+    ///
+    /// # Returns
+    /// - An error if the handshake is not complete yet (`MBEDTLS_ERR_SSL_BAD_INPUT_DATA`),
+    ///   or if MbedTLS is built without `MBEDTLS_SSL_KEYING_MATERIAL_EXPORT` (`MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE`)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), SessionError> {
+        export_keying_material(&self.state.ssl_context, label, context, out)
+    }
+
+    /// Get a snapshot of the parameters negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.state.connection_info()
+    }
+
+    /// Save the session negotiated with the server, so that it can be resumed later
+    /// by a new `DtlsSession` instance, with an abbreviated handshake
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to serialize the session into
+    ///
+    /// # Returns
+    /// - The length of the serialized session, or an error (`MBEDTLS_ERR_SSL_BUFFER_TOO_SMALL` if `buf` is too small)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn save_session(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.state.save_session(buf)
+    }
+
+    /// Load a session previously saved with `save_session`, so that it is resumed by the handshake
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `data` - The serialized session
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn load_session(&mut self, data: &[u8]) -> Result<(), SessionError> {
+        self.state.load_session(data)
+    }
+
+    /// Negotiate the DTLS connection
+    ///
+    /// This function will perform the DTLS handshake with the peer, retransmitting the handshake
+    /// messages as necessary.
+    ///
+    /// Note that calling it is not mandatory, because the DTLS session is anyway
+    /// negotiated during the first read or write operation.
+    pub async fn connect(&mut self) -> Result<(), SessionError> {
+        if self.connected {
+            return Ok(());
+        }
+
+        let (result, info) = loop {
+            self.state.reset()?;
+
+            if let Some(remote) = self.remote {
+                self.state.set_client_addr(remote)?;
+            }
+
+            let mut info = HandshakeInfo::default();
+
+            let result = self
+                .call_mbedtls(|ssl_ctx| unsafe { handshake(ssl_ctx, &mut info) })
+                .await;
+
+            if result != Ok(MBEDTLS_ERR_SSL_HELLO_VERIFY_REQUIRED) {
+                break (result, info);
+            }
+
+            // The client is expected to retry with the cookie of the HelloVerifyRequest
+            debug!("Sent a HelloVerifyRequest to {:?}", self.remote);
+        };
+
+        let result = result
+            .and_then(|ret| merr!(ret).map_err(|e| session_error(&self.state.ssl_context, e)));
+
+        self.state.handshake = info;
+
+        match result {
+            Ok(_) => {
+                self.connected = true;
+                self.eof = false;
+
+                Ok(())
+            }
+            Err(e) => Err(self.state.handshake_error(e)),
+        }
+    }
+
+    /// Read unencrypted data from the DTLS connection
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to read the data into. Data of the record not fitting in the buffer
+    ///   is returned by the next call.
+    ///
+    /// # Returns
+    /// - The number of bytes read (0 if the peer closed the connection) or an error
+    pub async fn read(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.connect().await?;
+
+        if self.eof || buf.is_empty() {
+            return Ok(0);
+        }
+
+        match self
+            .call_mbedtls(|ssl_ctx| unsafe {
+                mbedtls_ssl_read(ssl_ctx, buf.as_mut_ptr(), buf.len())
+            })
+            .await?
+        {
+            MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY => {
+                self.eof = true;
+                Ok(0)
+            }
+            other => {
+                let len = merr!(other).map_err(|e| session_error(&self.state.ssl_context, e))?;
+                Ok(len as usize)
+            }
+        }
+    }
+
+    /// Write unencrypted data to the DTLS connection
+    ///
+    /// # Arguments:
+    /// - `data` - The data to write, sent in a single record. Data larger than the maximum record payload
+    ///   (see `connection_info`) is rejected with `MBEDTLS_ERR_SSL_BAD_INPUT_DATA`.
+    ///
+    /// # Returns:
+    /// - The number of bytes written or an error
+    pub async fn write(&mut self, data: &[u8]) -> Result<usize, SessionError> {
+        self.connect().await?;
+
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        self.state.check_datagram_write(data)?;
+
+        let ret = self
+            .call_mbedtls(|ssl_ctx| unsafe {
+                mbedtls_ssl_write(ssl_ctx, data.as_ptr(), data.len())
+            })
+            .await?;
+
+        let len = merr!(ret).map_err(|e| session_error(&self.state.ssl_context, e))?;
+
+        Ok(len as usize)
+    }
+
+    /// Close the DTLS connection
+    ///
+    /// This function will close the DTLS connection, sending the "close notify" alert to the peer.
+    ///
+    /// # Returns:
+    /// - An error if the close failed
+    pub async fn close(&mut self) -> Result<(), SessionError> {
+        if !self.connected {
+            return Ok(());
+        }
+
+        merr!(
+            self.call_mbedtls(|ssl_ctx| unsafe { mbedtls_ssl_close_notify(ssl_ctx) })
+                .await?
+        )?;
+
+        self.connected = false;
+
+        Ok(())
+    }
+
+    /// Send an alert to the peer
+    ///
+    /// After sending a fatal alert, the session is considered closed.
+    ///
+    /// # Arguments
+    /// - `level` - The level of the alert
+    /// - `description` - The description of the alert
+    pub async fn send_alert(
+        &mut self,
+        level: AlertLevel,
+        description: AlertDescription,
+    ) -> Result<(), SessionError> {
+        merr!(
+            self.call_mbedtls(|ssl_ctx| unsafe {
+                mbedtls_ssl_send_alert_message(ssl_ctx, level.id(), description.id())
+            })
+            .await?
+        )?;
+
+        if level == AlertLevel::Fatal {
+            self.connected = false;
+        }
+
+        Ok(())
+    }
+
+    /// Call an MbedTLS function with the proper BIO callbacks set, until it no longer
+    /// needs to wait for the socket or for the retransmission timer
+    ///
+    /// # Returns
+    /// - The result of the MbedTLS function, or the error of the underlying socket
+    async fn call_mbedtls<F>(&mut self, f: F) -> Result<c_int, SessionError>
+    where
+        F: FnMut(&mut mbedtls_ssl_context) -> c_int,
+    {
+        call_with_bio(
+            &mut self.socket,
+            &mut self.remote,
+            &self.timer,
+            &mut self.state,
+            Inbound::Socket,
+            f,
+        )
+        .await
+    }
+}
+
+impl<S, T> Drop for DtlsSession<'_, S, T>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    fn drop(&mut self) {
+        if self.connected {
+            warn!("DTLS session dropped without being closed properly");
+        }
+
+        debug!("DTLS session dropped - freeing memory");
+    }
+}
+
+/// A DTLS server serving multiple clients over a single datagram socket represented by `edge-nal`'s
+/// `UdpSend` and `UdpReceive` traits.
+///
+/// The acceptor is itself a datagram socket implementing `UdpSend` and `UdpReceive`, which exchanges unencrypted
+/// data with the clients: `receive` returns the data of the next record received from any of the connected clients,
+/// along with the address of the client, while performing the handshakes and retransmissions in the background;
+/// `send` sends data in a single record to a connected client.
+///
+/// The datagrams received on the socket are demultiplexed by their DTLS connection ID, if one was negotiated
+/// with their client (see `ServerSessionConfig::connection_id_len`), and by their source address otherwise.
+/// With connection IDs, clients keep their session when their address changes, and are reported under their
+/// new address once a record was authenticated from it.
+///
+/// New clients have to go through the HelloVerifyRequest exchange first, using the cookie manager of the server
+/// configuration or - if not set - one of the acceptor. Thus, no session is allocated for a client
+/// until it has proven that it can receive datagrams at its address.
+///
+/// At most `N` clients are served at a time. New clients are turned down while all the slots are taken,
+/// i.e. until a client closes its session, fails its handshake, or is closed with `close`: once they
+/// completed the HelloVerifyRequest exchange, they receive a fatal `internal_error` alert instead of
+/// the first flight of the server.
+pub struct DtlsAcceptor<'d, S, T, const N: usize>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    /// The underlying datagram socket
+    socket: S,
+    /// The timer driving the retransmissions
+    timer: T,
+    /// The buffer the datagrams are received into, before being demultiplexed
+    buf: &'d mut [u8],
+    /// The maximum size of the datagrams sent to the clients
+    mtu: u16,
+    /// The server configuration, with a cookie manager set
+    config: SessionConfig<'d>,
+    /// The session processing the datagrams of unknown clients, up to their HelloVerifyRequest exchange
+    ///
+    /// Created on demand, and handed over to a client slot once a client passes the exchange.
+    listener: Option<SessionState<'d>>,
+    /// The client slots
+    peers: [Option<DtlsPeer<'d>>; N],
+    /// Reference to the active Tls instance
+    _token: TlsReference<'d>,
+}
+
+impl<'d, S, T, const N: usize> DtlsAcceptor<'d, S, T, N>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    /// Create a DTLS server over a datagram socket.
+    ///
+    /// # Arguments
+    /// - `tls` - A reference to the active `Tls` instance.
+    /// - `socket` - The datagram socket shared by all the clients, implementing `UdpSend` and `UdpReceive`.
+    /// - `timer` - The timer driving the retransmissions of the handshake messages.
+    /// - `buf` - The buffer the datagrams are received into, before being demultiplexed. Larger datagrams
+    ///   are truncated, so it should be as large as the largest datagram sent by the clients.
+    /// - `mtu` - The maximum size of the datagrams sent to the clients (i.e. the path MTU, minus the size
+    ///   of the IP and UDP headers), or 0 for no limit. The handshake messages are fragmented accordingly,
+    ///   and the application data sent in a single call must fit in one datagram.
+    /// - `config` - The server session configuration. DTLS only supports TLS 1.2 (i.e. DTLS 1.2), so `max_version`
+    ///   is capped accordingly.
+    ///
+    /// # Returns
+    /// - A `DtlsAcceptor` instance or an error on failure.
+    pub fn new(
+        tls: TlsReference<'d>,
+        socket: S,
+        timer: T,
+        buf: &'d mut [u8],
+        mtu: u16,
+        config: &ServerSessionConfig<'d>,
+    ) -> Result<Self, SessionError> {
+        let mut config = config.clone();
+
+        #[cfg(mbedtls_ssl_dtls_hello_verify)]
+        if config.dtls_cookies.is_none() {
+            config.dtls_cookies = Some(DtlsCookies::new(tls)?);
+        }
+
+        // Otherwise, there are not enough connection IDs for telling all the clients apart
+        if config
+            .connection_id_len
+            .is_some_and(|len| len > 0 && len < 8 && N as u64 >= 1 << (8 * len as u32))
+        {
+            Err(MbedtlsError::new(MBEDTLS_ERR_SSL_BAD_INPUT_DATA))?;
+        }
+
+        Ok(Self {
+            socket,
+            timer,
+            buf,
+            mtu,
+            config: SessionConfig::Server(config),
+            listener: None,
+            peers: core::array::from_fn(|_| None),
+            _token: tls,
+        })
+    }
+
+    /// Get a mutable reference to the underlying socket
+    pub fn socket(&mut self) -> &mut S {
+        &mut self.socket
+    }
+
+    /// Get the addresses of the connected clients
+    pub fn peers(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.peers
+            .iter()
+            .flatten()
+            .filter(|peer| peer.connected)
+            .map(|peer| peer.remote)
+    }
+
+    /// Get a snapshot of the parameters negotiated with a client
+    ///
+    /// # Arguments
+    /// - `remote` - The address of the client
+    ///
+    /// # Returns
+    /// - The negotiated parameters, or `None` if no client is connected from `remote`
+    pub fn connection_info(&self, remote: SocketAddr) -> Option<ConnectionInfo> {
+        self.peers
+            .iter()
+            .flatten()
+            .find(|peer| peer.connected && peer.remote == remote)
+            .map(|peer| peer.state.connection_info())
+    }
+
+    /// Close the session of a client
+    ///
+    /// If the client is connected, the "close notify" alert is sent to it. Either way, its slot is freed.
+    ///
+    /// # Arguments
+    /// - `remote` - The address of the client
+    ///
+    /// # Returns
+    /// - An error if there is no client at `remote`, or if the close failed
+    pub async fn close(&mut self, remote: SocketAddr) -> Result<(), SessionError> {
+        let index = self
+            .position(remote, false)
+            .ok_or(SessionError::Io(ErrorKind::NotConnected))?;
+
+        let Some(mut peer) = self.peers[index].take() else {
+            return Ok(());
+        };
+
+        if peer.connected {
+            merr!(
+                call_with_bio(
+                    &mut self.socket,
+                    &mut Some(peer.remote),
+                    &self.timer,
+                    &mut peer.state,
+                    Inbound::Demuxed(None),
+                    |ssl_ctx| unsafe { mbedtls_ssl_close_notify(ssl_ctx) },
+                )
+                .await?
+            )?;
+        }
+
+        debug!("Closed the DTLS session with {}", remote);
+
+        Ok(())
+    }
+
+    /// Find the slot of the client at the provided address
+    fn position(&self, remote: SocketAddr, connected: bool) -> Option<usize> {
+        self.peers.iter().position(|peer| {
+            peer.as_ref()
+                .is_some_and(|peer| peer.remote == remote && (peer.connected || !connected))
+        })
+    }
+
+    /// Find the slot of the client a datagram belongs to, by its connection ID or by its source address
+    ///
+    /// # Arguments
+    /// - `len` - The length of the datagram in `buf`
+    /// - `remote` - The source address of the datagram
+    fn demux(&self, len: usize, remote: SocketAddr) -> Option<usize> {
+        self.position_by_cid(&self.buf[..len])
+            .or_else(|| self.position(remote, false))
+    }
+
+    /// Find the slot of the client a datagram belongs to by its connection ID, if it has one
+    #[cfg(mbedtls_ssl_dtls_connection_id)]
+    fn position_by_cid(&self, datagram: &[u8]) -> Option<usize> {
+        // A DTLS 1.2 record with a connection ID has it right after the content type,
+        // the version, the epoch and the sequence number
+        let cid = (datagram.first() == Some(&(MBEDTLS_SSL_MSG_CID as u8)))
+            .then(|| datagram.get(11..))
+            .flatten()?;
+
+        self.peers.iter().position(|peer| {
+            peer.as_ref().is_some_and(|peer| {
+                let own_cid = own_cid(&peer.state.ssl_context);
+
+                !own_cid.is_empty() && cid.starts_with(own_cid)
+            })
+        })
+    }
+
+    #[cfg(not(mbedtls_ssl_dtls_connection_id))]
+    fn position_by_cid(&self, _datagram: &[u8]) -> Option<usize> {
+        None
+    }
+
+    /// Wait for a datagram on the socket, or for the provided deadline
+    ///
+    /// # Returns
+    /// - The length and source address of the datagram received into `buf`, or `None` if the deadline
+    ///   was reached first
+    async fn wait(
+        &mut self,
+        deadline: Option<Duration>,
+    ) -> Result<Option<(usize, SocketAddr)>, SessionError> {
+        let timer = &self.timer;
+
+        let mut receive = pin!(self.socket.receive(self.buf));
+        let mut timeout = pin!(async {
+            match deadline {
+                Some(deadline) => timer.wait_until(deadline).await,
+                None => core::future::pending().await,
+            }
+        });
+
+        poll_fn(|ctx| {
+            if let Poll::Ready(result) = receive.as_mut().poll(ctx) {
+                return Poll::Ready(result.map(Some).map_err(SessionError::from_io));
+            }
+
+            timeout.as_mut().poll(ctx).map(|_| Ok(None))
+        })
+        .await
+    }
+
+    /// Process a datagram of an unknown client with the listener session, which answers a ClientHello
+    /// without a valid cookie with a HelloVerifyRequest, and otherwise starts the handshake, in which case
+    /// the listener session is handed over to a free client slot, or turns the client down if there is none
+    ///
+    /// # Arguments
+    /// - `len` - The length of the datagram in `buf`
+    /// - `remote` - The source address of the datagram
+    async fn accept(&mut self, len: usize, remote: SocketAddr) -> Result<(), SessionError> {
+        let listener = match &mut self.listener {
+            Some(listener) => listener,
+            listener => {
+                let mut state = SessionState::new(&self.config, true)?;
+
+                unsafe {
+                    mbedtls_ssl_set_mtu(&mut *state.ssl_context, self.mtu);
+                }
+
+                listener.insert(state)
+            }
+        };
+
+        listener.reset()?;
+        listener.set_client_addr(remote)?;
+
+        // The connection ID of the listener is handed to the client in the ServerHello, so it has to be
+        // different from the ones of the other clients, as these are told apart by their connection IDs.
+        // All IDs have the same length, so the loop terminates as long as there are more IDs than slots
+        #[cfg(mbedtls_ssl_dtls_connection_id)]
+        while !own_cid(&listener.ssl_context).is_empty()
+            && self
+                .peers
+                .iter()
+                .flatten()
+                .any(|peer| own_cid(&peer.state.ssl_context) == own_cid(&listener.ssl_context))
+        {
+            debug!("Regenerating a connection ID already taken by another client");
+
+            let len = own_cid(&listener.ssl_context).len();
+
+            set_random_cid(&mut listener.ssl_context, len)?;
+        }
+
+        let free_slot = self.peers.iter().any(Option::is_none);
+
+        let mut info = HandshakeInfo::default();
+
+        let result = call_with_bio(
+            &mut self.socket,
+            &mut Some(remote),
+            &self.timer,
+            listener,
+            Inbound::Demuxed(Some(&self.buf[..len])),
+            |ssl_ctx| unsafe {
+                if free_slot {
+                    handshake(ssl_ctx, &mut info)
+                } else {
+                    turn_down(ssl_ctx)
+                }
+            },
+        )
+        .await?;
+
+        listener.handshake = info;
+
+        // Whether the ClientHello was accepted, as opposed to e.g. a stray record being discarded
+        let started = listener.ssl_context.private_state
+            > mbedtls_ssl_states_MBEDTLS_SSL_CLIENT_HELLO as c_int;
+
+        match result {
+            MBEDTLS_ERR_SSL_HELLO_VERIFY_REQUIRED => {
+                debug!("Sent a HelloVerifyRequest to {}", remote);
+            }
+            0 if !free_slot => {
+                warn!(
+                    "Turned down DTLS client {}, all {} slots are taken",
+                    remote, N
+                );
+            }
+            MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE if started && free_slot => {
+                if let Some(slot) = self.peers.iter_mut().find(|peer| peer.is_none()) {
+                    debug!("Starting a DTLS handshake with {}", remote);
+
+                    *slot = self.listener.take().map(|state| DtlsPeer {
+                        state,
+                        remote,
+                        connected: false,
+                    });
+                }
+            }
+            MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE => {
+                debug!(
+                    "Discarding a {}B datagram from unknown peer {}",
+                    len, remote
+                );
+            }
+            other => {
+                let err = session_error(&listener.ssl_context, MbedtlsError::new(other));
+                debug!(
+                    "Discarding a {}B datagram from unknown peer {}: {:?}",
+                    len, remote, err
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Have the session of a client process its next datagram, its pending records or its expired retransmission timer
+    ///
+    /// Handshake and session failures free the slot of the client, and are only logged.
+    ///
+    /// # Arguments
+    /// - `index` - The slot of the client
+    /// - `datagram` - The length (in `self.buf`) and source address of the datagram to process, if any
+    /// - `buf` - The buffer to read the application data into
+    ///
+    /// # Returns
+    /// - The length of the application data read into `buf` along with the address of the client, if any,
+    ///   or the error of the underlying socket
+    async fn process(
+        &mut self,
+        index: usize,
+        datagram: Option<(usize, SocketAddr)>,
+        buf: &mut [u8],
+    ) -> Result<Option<(usize, SocketAddr)>, SessionError> {
+        let Some(peer) = self.peers[index].as_mut() else {
+            return Ok(None);
+        };
+
+        let mut inbound = Inbound::Demuxed(datagram.map(|(len, _)| &self.buf[..len]));
+
+        loop {
+            if !peer.connected {
+                let mut info = peer.state.handshake;
+
+                let result = call_with_bio(
+                    &mut self.socket,
+                    &mut Some(peer.remote),
+                    &self.timer,
+                    &mut peer.state,
+                    inbound,
+                    |ssl_ctx| unsafe { handshake(ssl_ctx, &mut info) },
+                )
+                .await?;
+
+                peer.state.handshake = info;
+
+                match result {
+                    0 => {
+                        debug!("DTLS handshake with {} complete", peer.remote);
+                        peer.connected = true;
+                    }
+                    MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE => (),
+                    other => {
+                        let err = session_error(&peer.state.ssl_context, MbedtlsError::new(other));
+                        let err = peer.state.handshake_error(err);
+
+                        warn!("DTLS handshake with {} failed: {:?}", peer.remote, err);
+
+                        self.peers[index] = None;
+                    }
+                }
+
+                break Ok(None);
+            }
+
+            let result = call_with_bio(
+                &mut self.socket,
+                &mut Some(peer.remote),
+                &self.timer,
+                &mut peer.state,
+                inbound,
+                |ssl_ctx| unsafe { mbedtls_ssl_read(ssl_ctx, buf.as_mut_ptr(), buf.len()) },
+            )
+            .await?;
+
+            match result {
+                len @ 1.. => {
+                    if let Some((_, from)) = datagram.filter(|(_, from)| *from != peer.remote) {
+                        // Only possible with a connection ID, which authenticated the record
+                        debug!("DTLS client {} moved to {}", peer.remote, from);
+                        peer.remote = from;
+                    }
+
+                    break Ok(Some((len as usize, peer.remote)));
+                }
+                MBEDTLS_ERR_SSL_CLIENT_RECONNECT => {
+                    // The client started a new handshake from the same address, and MbedTLS
+                    // kept its ClientHello for the handshake
+                    debug!("DTLS client {} reconnected", peer.remote);
+
+                    peer.connected = false;
+                    peer.state.handshake = HandshakeInfo::default();
+                    inbound = Inbound::Demuxed(None);
+                }
+                MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY => {
+                    debug!("DTLS client {} closed the session", peer.remote);
+
+                    self.peers[index] = None;
+
+                    break Ok(None);
+                }
+                0 | MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE => break Ok(None),
+                other => {
+                    let err = session_error(&peer.state.ssl_context, MbedtlsError::new(other));
+
+                    warn!("DTLS session with {} failed: {:?}", peer.remote, err);
+
+                    self.peers[index] = None;
+
+                    break Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl<S, T, const N: usize> ErrorType for DtlsAcceptor<'_, S, T, N>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    type Error = SessionError;
+}
+
+impl<S, T, const N: usize> UdpReceive for DtlsAcceptor<'_, S, T, N>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    async fn receive(&mut self, buffer: &mut [u8]) -> Result<(usize, SocketAddr), Self::Error> {
+        loop {
+            // Records left over from the last datagram of a client come first
+            let pending = self.peers.iter().position(|peer| {
+                peer.as_ref().is_some_and(|peer| {
+                    peer.connected
+                        && unsafe { mbedtls_ssl_check_pending(&*peer.state.ssl_context) } != 0
+                })
+            });
+
+            if let Some(index) = pending {
+                if let Some(received) = self.process(index, None, buffer).await? {
+                    break Ok(received);
+                }
+
+                continue;
+            }
+
+            let deadline = self
+                .peers
+                .iter()
+                .flatten()
+                .filter_map(|peer| peer.state.retransmission_deadline())
+                .min();
+
+            if let Some((len, remote)) = self.wait(deadline).await? {
+                trace!("Receive {}B from {}", len, remote);
+
+                match self.demux(len, remote) {
+                    Some(index) => {
+                        if let Some(received) =
+                            self.process(index, Some((len, remote)), buffer).await?
+                        {
+                            break Ok(received);
+                        }
+                    }
+                    None => self.accept(len, remote).await?,
+                }
+            } else {
+                // Have MbedTLS retransmit the last flight of the handshakes whose timer expired
+                let now = self.timer.now();
+
+                for index in 0..N {
+                    let expired = self.peers[index]
+                        .as_ref()
+                        .and_then(|peer| peer.state.retransmission_deadline())
+                        .is_some_and(|deadline| deadline <= now);
+
+                    if expired {
+                        self.process(index, None, buffer).await?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S, T, const N: usize> UdpSend for DtlsAcceptor<'_, S, T, N>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    async fn send(&mut self, remote: SocketAddr, data: &[u8]) -> Result<(), Self::Error> {
+        let Some(peer) = self
+            .position(remote, true)
+            .and_then(|index| self.peers[index].as_mut())
+        else {
+            return Err(SessionError::Io(ErrorKind::NotConnected));
+        };
+
+        peer.state.check_datagram_write(data)?;
+
+        let ret = call_with_bio(
+            &mut self.socket,
+            &mut Some(peer.remote),
+            &self.timer,
+            &mut peer.state,
+            Inbound::Demuxed(None),
+            |ssl_ctx| unsafe { mbedtls_ssl_write(ssl_ctx, data.as_ptr(), data.len()) },
+        )
+        .await?;
+
+        merr!(ret).map_err(|e| session_error(&peer.state.ssl_context, e))?;
+
+        Ok(())
+    }
+}
+
+impl<S, T, const N: usize> Drop for DtlsAcceptor<'_, S, T, N>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+{
+    fn drop(&mut self) {
+        if self.peers().next().is_some() {
+            warn!("DTLS acceptor dropped without closing the sessions of its clients properly");
+        }
+
+        debug!("DTLS acceptor dropped - freeing memory");
+    }
+}
+
+/// A client of a `DtlsAcceptor`
+struct DtlsPeer<'d> {
+    /// The session state
+    state: SessionState<'d>,
+    /// The address of the client
+    remote: SocketAddr,
+    /// Whether the handshake is complete
+    connected: bool,
+}
+
+/// Process the ClientHello of a client which cannot be served, because all the slots are taken
+///
+/// A ClientHello without a valid cookie is still answered with a HelloVerifyRequest, so that the alert
+/// turning the client down is only sent to a verified address. Otherwise, the handshake is aborted with
+/// a fatal `internal_error` alert right after the ClientHello is processed, i.e. before the first flight
+/// of the server is sent.
+///
+/// # Returns
+/// - 0 if the alert was sent, `MBEDTLS_ERR_SSL_HELLO_VERIFY_REQUIRED` if a HelloVerifyRequest was sent,
+///   or the error of the handshake step which processed the datagram
+///
+/// # Safety
+/// `ssl_context` must point to a valid, set up SSL context with BIO callbacks set
+unsafe fn turn_down(ssl_context: *mut mbedtls_ssl_context) -> c_int {
+    loop {
+        let state = (*ssl_context).private_state as mbedtls_ssl_states;
+
+        if state > mbedtls_ssl_states_MBEDTLS_SSL_SERVER_HELLO
+            && state != mbedtls_ssl_states_MBEDTLS_SSL_SERVER_HELLO_VERIFY_REQUEST_SENT
+        {
+            break mbedtls_ssl_send_alert_message(
+                ssl_context,
+                AlertLevel::Fatal.id(),
+                AlertDescription::InternalError.id(),
+            );
+        }
+
+        let ret = mbedtls_ssl_handshake_step(ssl_context);
+
+        if ret != 0 {
+            break ret;
+        }
+    }
+}
+
+/// Call an MbedTLS function on the state of a DTLS session with the proper BIO callbacks set, until it
+/// no longer needs to wait for the socket or for the retransmission timer
+///
+/// # Arguments
+/// - `socket` - The datagram socket
+/// - `remote` - The address of the peer; `None` binds the session to the address of the first datagram received
+/// - `timer` - The timer driving the retransmissions
+/// - `state` - The session state
+/// - `inbound` - Where the datagrams of the peer come from
+/// - `f` - The MbedTLS function
+///
+/// # Returns
+/// - The result of the MbedTLS function, or the error of the socket. With `Inbound::Demuxed`, the result is
+///   `MBEDTLS_ERR_SSL_WANT_READ` if the MbedTLS function needs more datagrams than the one provided.
+async fn call_with_bio<S, T, F>(
+    socket: &mut S,
+    remote: &mut Option<SocketAddr>,
+    timer: &T,
+    state: &mut SessionState<'_>,
+    mut inbound: Inbound<'_>,
+    mut f: F,
+) -> Result<c_int, SessionError>
+where
+    S: UdpSend + UdpReceive,
+    T: DtlsTimer,
+    F: FnMut(&mut mbedtls_ssl_context) -> c_int,
+{
+    // The wait for the retransmission deadline, kept across polls until it completes or the deadline changes
+    let mut wait = pin!(None);
+    let mut wait_deadline = None;
+
+    poll_fn(|ctx| loop {
+        state.sample_time(timer.now());
+
+        let ssl_context = &mut *state.ssl_context as *mut mbedtls_ssl_context;
+
+        let mut io = DtlsIo {
+            socket: &mut *socket,
+            remote: &mut *remote,
+            ssl_context,
+            inbound,
+            ctx: &mut *ctx,
+            pending: false,
+            error: None,
+        };
+
+        unsafe {
+            mbedtls_ssl_set_bio(
+                ssl_context,
+                &mut io as *mut DtlsIo<'_, '_, '_, S> as *mut c_void,
+                Some(DtlsIo::<S>::raw_send),
+                Some(DtlsIo::<S>::raw_receive),
+                None,
+            );
+        }
+
+        let result = f(unsafe { &mut *ssl_context });
+
+        // Remove the callbacks so that we get a warning from MbedTLS in case
+        // it needs to invoke them when we don't anticipate so (for bugs detection)
+        unsafe {
+            mbedtls_ssl_set_bio(ssl_context, core::ptr::null_mut(), None, None, None);
+        }
+
+        inbound = io.inbound;
+
+        if let Some(err) = io.error {
+            break Poll::Ready(Err(err));
+        }
+
+        if !matches!(
+            result,
+            MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE
+        ) {
+            break Poll::Ready(Ok(result));
+        }
+
+        if !io.pending {
+            if let Inbound::Demuxed(None) = inbound {
+                // The datagram was consumed, and waiting for the next one is up to the `DtlsAcceptor`
+                break Poll::Ready(Ok(result));
+            }
+
+            // MbedTLS did not wait on the socket (e.g. because it just retransmitted
+            // its last flight), so call it again
+            continue;
+        }
+
+        if let Some(deadline) = state.retransmission_deadline() {
+            if wait_deadline != Some(deadline) {
+                wait.set(Some(timer.wait_until(deadline)));
+                wait_deadline = Some(deadline);
+            }
+
+            if let Some(Poll::Ready(())) = wait.as_mut().as_pin_mut().map(|wait| wait.poll(ctx)) {
+                wait.set(None);
+                wait_deadline = None;
+
+                // Have MbedTLS retransmit its last flight
+                continue;
+            }
+        }
+
+        break Poll::Pending;
+    })
+    .await
+}
+
+/// Where the BIO receive callback of a DTLS session gets the datagrams of the peer from
+#[derive(Clone, Copy)]
+enum Inbound<'a> {
+    /// From the socket, which is dedicated to the session
+    Socket,
+    /// From the `DtlsAcceptor` which demultiplexed it - if any is left - from the socket shared by its clients
+    Demuxed(Option<&'a [u8]>),
+}
+
+/// The context passed to the MbedTLS BIO callbacks of a DTLS session.
+///
+/// Unlike with the stream-based `MBio`, no state needs to be carried over between the BIO callbacks
+/// and the async context of the session, as datagrams are sent and received as a whole.
+struct DtlsIo<'a, 'c, 'd, S> {
+    /// The underlying datagram socket
+    socket: &'a mut S,
+    /// The address of the peer
+    remote: &'a mut Option<SocketAddr>,
+    /// The SSL context, for binding the HelloVerifyRequest cookies to the address of the peer once it is known
+    ssl_context: *mut mbedtls_ssl_context,
+    /// Where the datagrams of the peer come from
+    inbound: Inbound<'d>,
+    /// The async context, for polling the socket from within the BIO callbacks
+    ctx: &'a mut Context<'c>,
+    /// Whether the socket was polled and is not ready, i.e. whether the waker of the async context
+    /// is registered with the socket
+    pending: bool,
+    /// The error returned by the socket, if any
+    error: Option<SessionError>,
+}
+
+impl<S> DtlsIo<'_, '_, '_, S>
+where
+    S: UdpSend + UdpReceive,
+{
+    /// The MbedTLS BIO receive callback
+    fn bio_receive(&mut self, buf: &mut [u8]) -> c_int {
+        if let Inbound::Demuxed(datagram) = &mut self.inbound {
+            let Some(datagram) = datagram.take() else {
+                return MBEDTLS_ERR_SSL_WANT_READ;
+            };
+
+            let len = datagram.len().min(buf.len());
+            buf[..len].copy_from_slice(&datagram[..len]);
+
+            trace!("Receive {}B", len);
+
+            return len as _;
+        }
+
+        loop {
+            let mut fut = pin!(self.socket.receive(buf));
+
+            match fut.as_mut().poll(self.ctx) {
+                Poll::Ready(Ok((len, remote))) => match *self.remote {
+                    Some(bound) if bound != remote => {
+                        debug!("Discarding a {}B datagram from an unknown peer", len);
+                    }
+                    Some(_) => {
+                        trace!("Receive {}B", len);
+                        break len as _;
+                    }
+                    None => {
+                        // A server session is bound to the address of the first datagram received
+                        *self.remote = Some(remote);
+
+                        trace!("Receive {}B", len);
+
+                        let ret = unsafe { set_client_transport_id(self.ssl_context, remote) };
+
+                        break if ret != 0 { ret } else { len as _ };
+                    }
+                },
+                Poll::Ready(Err(e)) => {
+                    self.error = Some(SessionError::from_io(e));
+                    break MBEDTLS_ERR_SSL_INTERNAL_ERROR;
+                }
+                Poll::Pending => {
+                    self.pending = true;
+                    break MBEDTLS_ERR_SSL_WANT_READ;
+                }
+            }
+        }
+    }
+    /// The MbedTLS BIO send callback
+    fn bio_send(&mut self, data: &[u8]) -> c_int {
+        let Some(remote) = *self.remote else {
+            self.error = Some(SessionError::Io(ErrorKind::NotConnected));
+            return MBEDTLS_ERR_SSL_INTERNAL_ERROR;
+        };
+
+        trace!("Send {}B", data.len());
+
+        let mut fut = pin!(self.socket.send(remote, data));
+
+        match fut.as_mut().poll(self.ctx) {
+            Poll::Ready(Ok(())) => data.len() as _,
+            Poll::Ready(Err(e)) => {
+                self.error = Some(SessionError::from_io(e));
+                MBEDTLS_ERR_SSL_INTERNAL_ERROR
+            }
+            Poll::Pending => {
+                self.pending = true;
+                MBEDTLS_ERR_SSL_WANT_WRITE
+            }
+        }
+    }
+
+    /// The raw MbedTLS BIO receive callback
+    unsafe extern "C" fn raw_receive(ctx: *mut c_void, buf: *mut c_uchar, len: usize) -> c_int {
+        let io = (ctx as *mut Self).as_mut().unwrap();
+
+        io.bio_receive(core::slice::from_raw_parts_mut(buf as *mut _, len))
+    }
+
+    /// The raw MbedTLS BIO send callback
+    unsafe extern "C" fn raw_send(ctx: *mut c_void, buf: *const c_uchar, len: usize) -> c_int {
+        let io = (ctx as *mut Self).as_mut().unwrap();
+
+        io.bio_send(core::slice::from_raw_parts(buf as *const _, len))
+    }
+}
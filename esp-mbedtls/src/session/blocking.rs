@@ -1,10 +1,22 @@
 use core::ffi::{c_int, c_uchar, c_void, CStr};
 
+use enumset::EnumSet;
+
 use io::{ErrorType, Read, Write};
 
 use crate::sys::*;
 
-use super::{SessionConfig, SessionError, SessionState, TlsReference};
+use super::{
+    discard_early_data, early_data_status, export_keying_material, handshake, negotiated_alpn,
+    negotiated_ciphersuite, negotiated_version, peer_certificate, read_early_data, session_error,
+    verification_details, write_early_data, AlertDescription, AlertLevel, CertificateRef,
+    Ciphersuite, ConnectionInfo, EarlyDataStatus, NamedGroup, SessionConfig, SessionError,
+    SessionState, TlsReference, TlsVersion, VerifyFlag,
+};
+
+pub use dtls::*;
+
+mod dtls;
 
 /// Re-export of the `embedded-io` crate so that users don't have to explicitly depend on it
 /// to use e.g. `write_all` or `read_exact`.
@@ -49,7 +61,7 @@ where
     ) -> Result<Self, SessionError> {
         Ok(Self {
             stream,
-            state: SessionState::new(config)?,
+            state: SessionState::new(config, false)?,
             connected: false,
             eof: false,
             _tls_ref: tls,
@@ -61,6 +73,41 @@ where
         &mut self.stream
     }
 
+    /// Save the session negotiated with the server, so that it can be resumed later
+    /// by a new `Session` instance, with an abbreviated handshake
+    ///
+    /// The saved session can be persisted (e.g. in RTC memory or flash) and restored
+    /// with `load_session`. Both TLS 1.2 sessions (session IDs and tickets) and TLS 1.3
+    /// session tickets are supported. Note that TLS 1.3 servers send their session tickets
+    /// after the handshake, so these are only received once data is read from the session.
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to serialize the session into
+    ///
+    /// # Returns
+    /// - The length of the serialized session, or an error (`MBEDTLS_ERR_SSL_BUFFER_TOO_SMALL` if `buf` is too small)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn save_session(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.state.save_session(buf)
+    }
+
+    /// Load a session previously saved with `save_session`, so that it is resumed by the handshake
+    ///
+    /// If the server does not agree to resume the session, a full handshake is performed instead.
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `data` - The serialized session
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn load_session(&mut self, data: &[u8]) -> Result<(), SessionError> {
+        self.state.load_session(data)
+    }
+
     /// Set the server name for the TLS connection
     ///
     /// # Arguments
@@ -84,37 +131,273 @@ where
             return Ok(());
         }
 
-        merr!(unsafe { mbedtls_ssl_session_reset(&mut *self.state.ssl_context) })?;
+        self.handshake(false)?;
+
+        Ok(())
+    }
+
+    /// Write TLS 1.3 early data (0-RTT), i.e. data sent to the server along with the first
+    /// handshake messages, before the handshake is complete
+    ///
+    /// Client sessions with `early_data` enabled only. The handshake is started if necessary,
+    /// and completed by a subsequent `connect()` call (or by the first read or write operation).
+    ///
+    /// Once the handshake is complete, `early_data_status` tells whether the server accepted the
+    /// early data. If it did not, the data has to be written again with `write()`.
+    ///
+    /// Note that early data is not protected against replays, so it should only carry idempotent requests.
+    ///
+    /// # Arguments
+    /// - `data` - The data to write
+    ///
+    /// # Returns
+    /// - The number of bytes written, 0 if early data cannot be written (e.g. because no session was loaded
+    ///   with `load_session`, because its ticket does not allow early data, because the maximum
+    ///   amount of early data accepted by the server is reached, or because the handshake is complete)
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn write_early_data(&mut self, data: &[u8]) -> Result<usize, SessionError> {
+        if self.connected {
+            return Ok(0);
+        }
+
+        if !self.state.in_handshake {
+            self.state.reset()?;
+            self.state.in_handshake = true;
+        }
+
+        let data = self.state.limit_write(data);
 
         loop {
-            match self.call_mbedtls(|ssl_ctx| unsafe { mbedtls_ssl_handshake(ssl_ctx) }) {
+            match self.call_mbedtls(|ssl_ctx| unsafe { write_early_data(ssl_ctx, data) }) {
+                MBEDTLS_ERR_SSL_WANT_READ => continue,
+                MBEDTLS_ERR_SSL_WANT_WRITE => continue,
+                MBEDTLS_ERR_SSL_CANNOT_WRITE_EARLY_DATA => break Ok(0),
+                other => {
+                    let len = merr!(other).map_err(|e| {
+                        let err = session_error(&self.state.ssl_context, e);
+                        self.state.in_handshake = false;
+                        self.state.handshake_error(err)
+                    })?;
+
+                    break Ok(len as usize);
+                }
+            }
+        }
+    }
+
+    /// Read TLS 1.3 early data (0-RTT) sent by the client
+    ///
+    /// Server sessions with `max_early_data_size` set only. The handshake is performed until
+    /// early data is received from the client, or until the handshake is complete.
+    /// This function should thus be called repeatedly until it returns 0, at which point
+    /// the handshake is complete. Early data not read with this function is discarded.
+    ///
+    /// Note that early data is not protected against replays, so it should only carry idempotent requests.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to read the early data into
+    ///
+    /// # Returns
+    /// - The number of bytes read, 0 if all early data (if any) was read and the handshake is complete
+    ///
+    /// NOTE: This function should be called instead of - or before - the `connect()` call.
+    pub fn read_early_data(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        while !self.connected {
+            if self.state.in_handshake {
+                let len = read_early_data(&mut self.state.ssl_context, buf)?;
+                if len > 0 {
+                    return Ok(len);
+                }
+            }
+
+            self.handshake(true)?;
+        }
+
+        Ok(0)
+    }
+
+    /// Get the status of the TLS 1.3 early data (0-RTT) written with `write_early_data`
+    ///
+    /// Client sessions only.
+    ///
+    /// # Returns
+    /// - The early data status, or `None` if this is a server session, if the handshake is not complete,
+    ///   or if MbedTLS is built without `MBEDTLS_SSL_EARLY_DATA`
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn early_data_status(&self) -> Option<EarlyDataStatus> {
+        early_data_status(&self.state.ssl_context)
+    }
+
+    /// Perform - or continue - the TLS handshake
+    ///
+    /// # Arguments
+    /// - `early_data` - Whether to interrupt the handshake when early data is received from the client,
+    ///   rather than discarding it
+    ///
+    /// # Returns
+    /// - `true` if the handshake was interrupted because early data was received, `false` if it is complete
+    fn handshake(&mut self, early_data: bool) -> Result<bool, SessionError> {
+        if !self.state.in_handshake {
+            self.state.reset()?;
+            self.state.in_handshake = true;
+        } else if !early_data {
+            discard_early_data(&mut self.state.ssl_context);
+        }
+
+        let mut info = self.state.handshake;
+
+        let result = loop {
+            match self.call_mbedtls(|ssl_ctx| unsafe { handshake(ssl_ctx, &mut info) }) {
                 MBEDTLS_ERR_SSL_WANT_READ => continue,
                 MBEDTLS_ERR_SSL_WANT_WRITE => continue,
                 // See https://github.com/Mbed-TLS/mbedtls/issues/8749
                 MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
+                MBEDTLS_ERR_SSL_RECEIVED_EARLY_DATA if early_data => break Ok(true),
+                MBEDTLS_ERR_SSL_RECEIVED_EARLY_DATA => {
+                    discard_early_data(&mut self.state.ssl_context)
+                }
                 other => {
-                    merr!(other)?;
+                    break merr!(other)
+                        .map(|_| false)
+                        .map_err(|e| session_error(&self.state.ssl_context, e))
+                }
+            }
+        };
 
-                    self.connected = true;
-                    self.eof = false;
+        self.state.handshake = info;
 
-                    break Ok(());
-                }
+        match result {
+            Ok(true) => Ok(true),
+            Ok(false) => {
+                self.state.in_handshake = false;
+                self.connected = true;
+                self.eof = false;
+
+                Ok(false)
+            }
+            Err(e) => {
+                self.state.in_handshake = false;
+
+                Err(self.state.handshake_error(e))
             }
         }
     }
 
     /// Get the TLS verification details
     ///
-    /// The details are a bitmask of various flags indicating the result of the certificate verification.
+    /// The details are a set of flags indicating the result of the certificate verification.
+    ///
+    /// # Returns
+    /// - An empty set if verification succeeded
+    /// - The verification failure flags otherwise (all flags if no verification result is available,
+    ///   e.g. because the handshake was aborted early)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn tls_verification_details(&self) -> EnumSet<VerifyFlag> {
+        verification_details(&self.state.ssl_context)
+    }
+
+    /// Get the certificate presented by the peer during the handshake
+    ///
+    /// The returned certificate is the first one of the peer certificate chain; the rest of the chain
+    /// is linked from it.
+    ///
+    /// # Returns
+    /// - The peer certificate, or `None` if the peer did not present a certificate
+    ///   (e.g. a client when client authentication is not requested)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn peer_certificate(&self) -> Option<CertificateRef<'_>> {
+        peer_certificate(&self.state.ssl_context)
+    }
+
+    /// Get the server name (SNI) requested by the client
+    ///
+    /// Server sessions only.
+    ///
+    /// # Returns
+    /// - The server name, or `None` if the client did not request a server name or this is a client session
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn server_name(&self) -> Option<&str> {
+        self.state.sni.as_ref().and_then(|sni| sni.server_name())
+    }
+
+    /// Get the ALPN protocol negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated protocol, or `None` if ALPN was not configured or the peer did not agree on a protocol
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_alpn(&self) -> Option<&CStr> {
+        negotiated_alpn(&self.state.ssl_context)
+    }
+
+    /// Get the TLS version negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_version(&self) -> TlsVersion {
+        negotiated_version(&self.state.ssl_context)
+    }
+
+    /// Get the ciphersuite negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated ciphersuite, or `None` if the handshake is not complete yet
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn ciphersuite(&self) -> Option<Ciphersuite> {
+        negotiated_ciphersuite(&self.state.ssl_context)
+    }
+
+    /// Get the (EC)DHE group negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated group, or `None` if the handshake is not complete yet or did not involve
+    ///   an ephemeral (EC)DHE key exchange with a named group (e.g. RSA or plain PSK key exchange,
+    ///   TLS 1.2 finite-field DHE, or an abbreviated TLS 1.2 resumption handshake)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn group(&self) -> Option<NamedGroup> {
+        self.state.handshake.group
+    }
+
+    /// Export keying material bound to the session, e.g. for channel binding
+    ///
+    /// The keying material is derived as per RFC 5705 for TLS 1.2, and as per RFC 8446 section 7.5
+    /// for TLS 1.3, so that both peers obtain the same value for the same label and context.
+    ///
+    /// # Arguments
+    /// - `label` - The label identifying the usage of the keying material (e.g. `EXPORTER-my-protocol`)
+    /// - `context` - The optional context value; for TLS 1.2, no context and an empty context
+    ///   result in different keying material
+    /// - `out` - The buffer to fill with the keying material
     ///
     /// # Returns
-    /// - 0 if verification succeeded
-    /// - A bitmask of verification failure flags otherwise
+    /// - An error if the handshake is not complete yet (`MBEDTLS_ERR_SSL_BAD_INPUT_DATA`),
+    ///   if `out` is larger than what MbedTLS supports, or if MbedTLS is built without
+    ///   `MBEDTLS_SSL_KEYING_MATERIAL_EXPORT` (`MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE`)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), SessionError> {
+        export_keying_material(&self.state.ssl_context, label, context, out)
+    }
+
+    /// Get a snapshot of the parameters negotiated with the peer
+    ///
+    /// Meant for logging and telemetry; all the information is also available via the
+    /// individual accessors of the session.
     ///
     /// NOTE: This function should be called only after a `connect()` call.
-    pub fn tls_verification_details(&self) -> u32 {
-        unsafe { mbedtls_ssl_get_verify_result(&*self.state.ssl_context) }
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.state.connection_info()
     }
 
     /// Read unencrypted data from the TLS connection
@@ -137,13 +420,18 @@ where
             }) {
                 MBEDTLS_ERR_SSL_WANT_READ => continue,
                 // See https://github.com/Mbed-TLS/mbedtls/issues/8749
-                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
+                // The ticket is retained in the SSL context and can be exported with `save_session`
+                MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => {
+                    debug!("Received a new TLS 1.3 session ticket");
+                    continue;
+                }
                 MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY => {
                     self.eof = true;
                     break Ok(0);
                 }
                 other => {
-                    let len = merr!(other)?;
+                    let len =
+                        merr!(other).map_err(|e| session_error(&self.state.ssl_context, e))?;
                     break Ok(len as usize);
                 }
             }
@@ -160,6 +448,8 @@ where
     pub fn write(&mut self, data: &[u8]) -> Result<usize, SessionError> {
         self.connect()?;
 
+        let data = self.state.limit_write(data);
+
         loop {
             match self.call_mbedtls(|ssl_ctx| unsafe {
                 mbedtls_ssl_write(ssl_ctx as *const _ as *mut _, data.as_ptr(), data.len())
@@ -168,7 +458,8 @@ where
                 // See https://github.com/Mbed-TLS/mbedtls/issues/8749
                 MBEDTLS_ERR_SSL_RECEIVED_NEW_SESSION_TICKET => continue,
                 other => {
-                    let len = merr!(other)?;
+                    let len =
+                        merr!(other).map_err(|e| session_error(&self.state.ssl_context, e))?;
                     break Ok(len as usize);
                 }
             }
@@ -209,6 +500,40 @@ where
         Ok(())
     }
 
+    /// Send an alert to the peer
+    ///
+    /// Useful for rejecting the peer with a specific alert (e.g. `access_denied`) before closing
+    /// the connection. After sending a fatal alert, the session is considered closed.
+    ///
+    /// # Arguments
+    /// - `level` - The level of the alert
+    /// - `description` - The description of the alert
+    pub fn send_alert(
+        &mut self,
+        level: AlertLevel,
+        description: AlertDescription,
+    ) -> Result<(), SessionError> {
+        loop {
+            match self.call_mbedtls(|ssl| unsafe {
+                mbedtls_ssl_send_alert_message(ssl, level.id(), description.id())
+            }) {
+                MBEDTLS_ERR_SSL_WANT_WRITE => continue,
+                other => {
+                    merr!(other)?;
+                    break;
+                }
+            }
+        }
+
+        self.stream.flush().map_err(SessionError::from_io)?;
+
+        if level == AlertLevel::Fatal {
+            self.connected = false;
+        }
+
+        Ok(())
+    }
+
     /// Helper function to call MbedTLS functions with BIO callbacks set
     fn call_mbedtls<F>(&mut self, mut f: F) -> c_int
     where
@@ -0,0 +1,516 @@
+use core::ffi::{c_int, c_uchar, c_void};
+use core::net::SocketAddr;
+use core::time::Duration;
+
+use enumset::EnumSet;
+
+use crate::sys::*;
+
+use super::super::{
+    export_keying_material, handshake, negotiated_ciphersuite, negotiated_version,
+    peer_certificate, session_error, verification_details, AlertDescription, AlertLevel,
+    CertificateRef, Ciphersuite, ConnectionInfo, HandshakeInfo, SessionConfig, SessionError,
+    SessionState, TlsReference, TlsVersion, VerifyFlag,
+};
+use super::io::ErrorType;
+
+/// A blocking datagram transport, connected to a single peer (e.g. a connected UDP socket)
+pub trait Datagram: ErrorType {
+    /// Send a datagram to the peer
+    ///
+    /// # Arguments
+    /// - `data` - The payload of the datagram
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Receive a datagram from the peer
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to receive the payload of the datagram into
+    /// - `timeout` - The maximum time to wait for a datagram, or `None` to wait indefinitely
+    ///
+    /// # Returns
+    /// - The length of the payload, or `None` if no datagram was received within `timeout`
+    fn receive(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<usize>, Self::Error>;
+}
+
+impl<T> Datagram for &mut T
+where
+    T: Datagram,
+{
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        (**self).send(data)
+    }
+
+    fn receive(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<usize>, Self::Error> {
+        (**self).receive(buf, timeout)
+    }
+}
+
+/// A monotonic clock for timing the retransmissions of the DTLS handshake messages
+///
+/// The retransmissions themselves are driven by the receive timeouts of the `Datagram` transport.
+pub trait DtlsTimer {
+    /// Get the current time
+    ///
+    /// # Returns
+    /// - The time elapsed since an arbitrary (but fixed) point in the past, e.g. since boot
+    fn now(&self) -> Duration;
+}
+
+impl<T> DtlsTimer for &T
+where
+    T: DtlsTimer,
+{
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// A blocking DTLS session over a datagram transport represented by the `Datagram` trait.
+///
+/// Unlike with TLS, the data of each `write` call is sent in a single record (and datagram),
+/// and each `read` call returns the data of at most one record.
+pub struct DtlsSession<'a, D, T>
+where
+    D: Datagram,
+    T: DtlsTimer,
+{
+    /// The underlying datagram transport
+    datagram: D,
+    /// The clock timing the retransmissions
+    timer: T,
+    /// The session state
+    state: SessionState<'a>,
+    /// Whether the session is connected
+    connected: bool,
+    /// Whether we received a close notify from the peer
+    eof: bool,
+    /// The address of the client, for server sessions
+    client_addr: Option<SocketAddr>,
+    /// The error returned by the datagram transport during the last call into MbedTLS, if any
+    error: Option<SessionError>,
+    /// Reference to the active Tls instance
+    _tls_ref: TlsReference<'a>,
+}
+
+impl<'a, D, T> DtlsSession<'a, D, T>
+where
+    D: Datagram,
+    T: DtlsTimer,
+{
+    /// Create a session for a DTLS connection.
+    ///
+    /// # Arguments
+    /// - `tls` - A reference to the active `Tls` instance.
+    /// - `datagram` - The datagram transport for the connection.
+    /// - `timer` - The clock timing the retransmissions of the handshake messages.
+    /// - `mtu` - The maximum size of the datagrams sent to the peer (i.e. the path MTU, minus the size
+    ///   of the IP and UDP headers), or 0 for no limit. The handshake messages are fragmented accordingly,
+    ///   and the application data written in a single call must fit in one datagram.
+    /// - `config` - The session configuration. DTLS only supports TLS 1.2 (i.e. DTLS 1.2), so `max_version`
+    ///   is capped accordingly.
+    ///
+    /// # Returns
+    /// - A `DtlsSession` instance or an error on failure.
+    pub fn new(
+        tls: TlsReference<'a>,
+        datagram: D,
+        timer: T,
+        mtu: u16,
+        config: &SessionConfig<'a>,
+    ) -> Result<Self, SessionError> {
+        let mut state = SessionState::new(config, true)?;
+
+        unsafe {
+            mbedtls_ssl_set_mtu(&mut *state.ssl_context, mtu);
+        }
+
+        Ok(Self {
+            datagram,
+            timer,
+            state,
+            connected: false,
+            eof: false,
+            client_addr: None,
+            error: None,
+            _tls_ref: tls,
+        })
+    }
+
+    /// Get a mutable reference to the underlying datagram transport
+    pub fn datagram(&mut self) -> &mut D {
+        &mut self.datagram
+    }
+
+    /// Set the address of the client, to which the HelloVerifyRequest cookies are bound
+    ///
+    /// Server sessions only. Mandatory if `dtls_cookies` is set in the server configuration,
+    /// as the address is not known to the `Datagram` transport.
+    ///
+    /// # Arguments
+    /// - `addr` - The address of the client
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn set_client_addr(&mut self, addr: SocketAddr) {
+        self.client_addr = Some(addr);
+    }
+
+    /// Get the DTLS verification details
+    ///
+    /// The details are a set of flags indicating the result of the certificate verification.
+    ///
+    /// # Returns
+    /// - An empty set if verification succeeded
+    /// - The verification failure flags otherwise (all flags if no verification result is available,
+    ///   e.g. because the handshake was aborted early)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn tls_verification_details(&self) -> EnumSet<VerifyFlag> {
+        verification_details(&self.state.ssl_context)
+    }
+
+    /// Get the certificate presented by the peer during the handshake
+    ///
+    /// # Returns
+    /// - The peer certificate, or `None` if the peer did not present a certificate
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn peer_certificate(&self) -> Option<CertificateRef<'_>> {
+        peer_certificate(&self.state.ssl_context)
+    }
+
+    /// Get the TLS version negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn negotiated_version(&self) -> TlsVersion {
+        negotiated_version(&self.state.ssl_context)
+    }
+
+    /// Get the ciphersuite negotiated with the peer
+    ///
+    /// # Returns
+    /// - The negotiated ciphersuite, or `None` if the handshake is not complete yet
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn ciphersuite(&self) -> Option<Ciphersuite> {
+        negotiated_ciphersuite(&self.state.ssl_context)
+    }
+
+    /// Export keying material bound to the session, as per RFC 5705
+    ///
+    /// # Arguments
+    /// - `label` - The label identifying the usage of the keying material
+    /// - `context` - The optional context value; no context and an empty context result in different keying material
+    /// - `out` - The buffer to fill with the keying material
+    ///
+    /// # Returns
+    /// - An error if the handshake is not complete yet (`MBEDTLS_ERR_SSL_BAD_INPUT_DATA`),
+    ///   or if MbedTLS is built without `MBEDTLS_SSL_KEYING_MATERIAL_EXPORT` (`MBEDTLS_ERR_SSL_FEATURE_UNAVAILABLE`)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn export_keying_material(
+        &self,
+        label: &str,
+        context: Option<&[u8]>,
+        out: &mut [u8],
+    ) -> Result<(), SessionError> {
+        export_keying_material(&self.state.ssl_context, label, context, out)
+    }
+
+    /// Get a snapshot of the parameters negotiated with the peer
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        self.state.connection_info()
+    }
+
+    /// Save the session negotiated with the server, so that it can be resumed later
+    /// by a new `DtlsSession` instance, with an abbreviated handshake
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to serialize the session into
+    ///
+    /// # Returns
+    /// - The length of the serialized session, or an error (`MBEDTLS_ERR_SSL_BUFFER_TOO_SMALL` if `buf` is too small)
+    ///
+    /// NOTE: This function should be called only after a `connect()` call.
+    pub fn save_session(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.state.save_session(buf)
+    }
+
+    /// Load a session previously saved with `save_session`, so that it is resumed by the handshake
+    ///
+    /// Client sessions only.
+    ///
+    /// # Arguments
+    /// - `data` - The serialized session
+    ///
+    /// NOTE: This function should be called before the `connect()` call.
+    pub fn load_session(&mut self, data: &[u8]) -> Result<(), SessionError> {
+        self.state.load_session(data)
+    }
+
+    /// Negotiate the DTLS connection
+    ///
+    /// This function will perform the DTLS handshake with the peer, retransmitting the handshake
+    /// messages as necessary.
+    ///
+    /// Note that calling it is not mandatory, because the DTLS session is anyway
+    /// negotiated during the first read or write operation.
+    pub fn connect(&mut self) -> Result<(), SessionError> {
+        if self.connected {
+            return Ok(());
+        }
+
+        let (result, info) = loop {
+            self.state.reset()?;
+
+            if let Some(addr) = self.client_addr {
+                self.state.set_client_addr(addr)?;
+            }
+
+            let mut info = HandshakeInfo::default();
+
+            let result = self.call_mbedtls(|ssl_ctx| unsafe { handshake(ssl_ctx, &mut info) });
+
+            if result != Ok(MBEDTLS_ERR_SSL_HELLO_VERIFY_REQUIRED) {
+                break (result, info);
+            }
+
+            // The client is expected to retry with the cookie of the HelloVerifyRequest
+            debug!("Sent a HelloVerifyRequest to {:?}", self.client_addr);
+        };
+
+        let result = result
+            .and_then(|ret| merr!(ret).map_err(|e| session_error(&self.state.ssl_context, e)));
+
+        self.state.handshake = info;
+
+        match result {
+            Ok(_) => {
+                self.connected = true;
+                self.eof = false;
+
+                Ok(())
+            }
+            Err(e) => Err(self.state.handshake_error(e)),
+        }
+    }
+
+    /// Read unencrypted data from the DTLS connection
+    ///
+    /// # Arguments
+    /// - `buf` - The buffer to read the data into. Data of the record not fitting in the buffer
+    ///   is returned by the next call.
+    ///
+    /// # Returns
+    /// - The number of bytes read (0 if the peer closed the connection) or an error
+    pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, SessionError> {
+        self.connect()?;
+
+        if self.eof || buf.is_empty() {
+            return Ok(0);
+        }
+
+        match self.call_mbedtls(|ssl_ctx| unsafe {
+            mbedtls_ssl_read(ssl_ctx, buf.as_mut_ptr(), buf.len())
+        })? {
+            MBEDTLS_ERR_SSL_PEER_CLOSE_NOTIFY => {
+                self.eof = true;
+                Ok(0)
+            }
+            other => {
+                let len = merr!(other).map_err(|e| session_error(&self.state.ssl_context, e))?;
+                Ok(len as usize)
+            }
+        }
+    }
+
+    /// Write unencrypted data to the DTLS connection
+    ///
+    /// # Arguments:
+    /// - `data` - The data to write, sent in a single record. Data larger than the maximum record payload
+    ///   (see `connection_info`) is rejected with `MBEDTLS_ERR_SSL_BAD_INPUT_DATA`.
+    ///
+    /// # Returns:
+    /// - The number of bytes written or an error
+    pub fn write(&mut self, data: &[u8]) -> Result<usize, SessionError> {
+        self.connect()?;
+
+        if data.is_empty() {
+            return Ok(0);
+        }
+
+        self.state.check_datagram_write(data)?;
+
+        let ret = self.call_mbedtls(|ssl_ctx| unsafe {
+            mbedtls_ssl_write(ssl_ctx, data.as_ptr(), data.len())
+        })?;
+
+        let len = merr!(ret).map_err(|e| session_error(&self.state.ssl_context, e))?;
+
+        Ok(len as usize)
+    }
+
+    /// Close the DTLS connection
+    ///
+    /// This function will close the DTLS connection, sending the "close notify" alert to the peer.
+    ///
+    /// # Returns:
+    /// - An error if the close failed
+    pub fn close(&mut self) -> Result<(), SessionError> {
+        if !self.connected {
+            return Ok(());
+        }
+
+        merr!(self.call_mbedtls(|ssl_ctx| unsafe { mbedtls_ssl_close_notify(ssl_ctx) })?)?;
+
+        self.connected = false;
+
+        Ok(())
+    }
+
+    /// Send an alert to the peer
+    ///
+    /// After sending a fatal alert, the session is considered closed.
+    ///
+    /// # Arguments
+    /// - `level` - The level of the alert
+    /// - `description` - The description of the alert
+    pub fn send_alert(
+        &mut self,
+        level: AlertLevel,
+        description: AlertDescription,
+    ) -> Result<(), SessionError> {
+        merr!(self.call_mbedtls(|ssl_ctx| unsafe {
+            mbedtls_ssl_send_alert_message(ssl_ctx, level.id(), description.id())
+        })?)?;
+
+        if level == AlertLevel::Fatal {
+            self.connected = false;
+        }
+
+        Ok(())
+    }
+
+    /// Helper function to call MbedTLS functions with BIO callbacks set, until these
+    /// no longer need to wait for the datagram transport
+    ///
+    /// # Returns
+    /// - The result of the MbedTLS function, or the error of the datagram transport
+    fn call_mbedtls<F>(&mut self, mut f: F) -> Result<c_int, SessionError>
+    where
+        F: FnMut(&mut mbedtls_ssl_context) -> c_int,
+    {
+        loop {
+            self.state.sample_time(self.timer.now());
+
+            unsafe {
+                mbedtls_ssl_set_bio(
+                    &mut *self.state.ssl_context as *mut _,
+                    self as *const _ as *mut Self as *mut c_void,
+                    Some(Self::raw_send),
+                    None,
+                    Some(Self::raw_receive_timeout),
+                );
+            }
+
+            let result = f(&mut self.state.ssl_context);
+
+            // Remove the callbacks so that we get a warning from MbedTLS in case
+            // it needs to invoke them when we don't anticipate so (for bugs detection)
+            unsafe {
+                mbedtls_ssl_set_bio(
+                    &mut *self.state.ssl_context as *mut _,
+                    core::ptr::null_mut(),
+                    None,
+                    None,
+                    None,
+                );
+            }
+
+            if let Some(err) = self.error.take() {
+                break Err(err);
+            }
+
+            match result {
+                // Returned after a retransmission, or after a datagram which did not contain a full record
+                MBEDTLS_ERR_SSL_WANT_READ | MBEDTLS_ERR_SSL_WANT_WRITE => continue,
+                other => break Ok(other),
+            }
+        }
+    }
+
+    /// The MbedTLS BIO receive callback
+    fn bio_receive_timeout(&mut self, buf: &mut [u8], timeout_ms: u32) -> c_int {
+        let timeout = (timeout_ms > 0).then(|| Duration::from_millis(timeout_ms as _));
+
+        let result = self.datagram.receive(buf, timeout);
+
+        // The wait might have taken a while, so that the retransmission timer has to be updated
+        self.state.sample_time(self.timer.now());
+
+        match result {
+            Ok(Some(len)) => len as c_int,
+            Ok(None) => MBEDTLS_ERR_SSL_TIMEOUT,
+            Err(e) => {
+                self.error = Some(SessionError::from_io(e));
+                MBEDTLS_ERR_SSL_INTERNAL_ERROR
+            }
+        }
+    }
+
+    /// The MbedTLS BIO send callback
+    fn bio_send(&mut self, data: &[u8]) -> c_int {
+        match self.datagram.send(data) {
+            Ok(()) => data.len() as c_int,
+            Err(e) => {
+                self.error = Some(SessionError::from_io(e));
+                MBEDTLS_ERR_SSL_INTERNAL_ERROR
+            }
+        }
+    }
+
+    /// The raw MbedTLS BIO receive callback
+    unsafe extern "C" fn raw_receive_timeout(
+        ctx: *mut c_void,
+        buf: *mut c_uchar,
+        len: usize,
+        timeout: u32,
+    ) -> c_int {
+        let session = (ctx as *mut Self).as_mut().unwrap();
+
+        session.bio_receive_timeout(core::slice::from_raw_parts_mut(buf as *mut _, len), timeout)
+    }
+
+    /// The raw MbedTLS BIO send callback
+    unsafe extern "C" fn raw_send(ctx: *mut c_void, buf: *const c_uchar, len: usize) -> c_int {
+        let session = (ctx as *mut Self).as_mut().unwrap();
+
+        session.bio_send(core::slice::from_raw_parts(buf as *const _, len))
+    }
+}
+
+impl<D, T> Drop for DtlsSession<'_, D, T>
+where
+    D: Datagram,
+    T: DtlsTimer,
+{
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            error!("Error during DTLS session close: {:?}", e);
+        }
+
+        debug!("DTLS session dropped - freeing memory");
+    }
+}
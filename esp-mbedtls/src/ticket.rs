@@ -0,0 +1,120 @@
+use core::ffi::c_void;
+
+use super::sys::*;
+use super::{mbedtls_rng, MInit, MRc, TlsReference};
+
+/// The length of a session ticket key name, in bytes
+pub const TICKET_KEY_NAME_LEN: usize = MBEDTLS_SSL_TICKET_KEY_NAME_BYTES as usize;
+
+/// The length of a session ticket key, in bytes (tickets are protected with AES-256-GCM)
+pub const TICKET_KEY_LEN: usize = 32;
+
+/// A server-side session ticket key manager, enabling stateless session resumption
+/// for TLS 1.2 session tickets and TLS 1.3 PSK-based resumption.
+///
+/// Instances are cheap to clone and all clones share the same keys, so that a single instance
+/// can be plugged into the `ServerSessionConfig` used by e.g. `TlsAcceptor`, and
+/// the tickets issued by any of the sessions are accepted by all others.
+///
+/// Tickets are protected with AES-256-GCM. The initial key is randomly generated; it can
+/// subsequently be replaced with [SessionTickets::rotate].
+#[derive(Clone)]
+pub struct SessionTickets<'d> {
+    pub(crate) ctx: MRc<mbedtls_ssl_ticket_context>,
+    _tls: TlsReference<'d>,
+}
+
+impl<'d> SessionTickets<'d> {
+    /// Create a new session ticket key manager with a randomly generated key
+    ///
+    /// # Arguments
+    /// - `tls` - A reference to the active `Tls` instance, which provides the RNG for the key generation
+    /// - `lifetime` - The lifetime of the issued tickets, in seconds. Note that TLS 1.3 mandates a lifetime
+    ///   of at most seven days, and that tickets can only expire if MbedTLS has access to the current time.
+    pub fn new(tls: TlsReference<'d>, lifetime: u32) -> Result<Self, MbedtlsError> {
+        let ctx = MRc::new().ok_or(MbedtlsError::new(MBEDTLS_ERR_SSL_ALLOC_FAILED))?;
+
+        merr!(unsafe {
+            mbedtls_ssl_ticket_setup(
+                &*ctx as *const _ as *mut _,
+                Some(mbedtls_rng),
+                core::ptr::null_mut(),
+                mbedtls_cipher_type_t_MBEDTLS_CIPHER_AES_256_GCM,
+                lifetime,
+            )
+        })?;
+
+        Ok(Self { ctx, _tls: tls })
+    }
+
+    /// Rotate the ticket protection key
+    ///
+    /// The previous key remains valid for parsing the tickets issued with it,
+    /// but new tickets are issued with the new key only.
+    ///
+    /// Useful e.g. for synchronizing the keys of multiple servers, or for bounding the key lifetime
+    /// when MbedTLS has no access to the current time and thus cannot rotate the keys by itself.
+    ///
+    /// # Arguments
+    /// - `name` - The name of the new key, which is embedded in the tickets. Should be random.
+    /// - `key` - The new key. Should be random.
+    /// - `lifetime` - The lifetime of the tickets issued with the new key, in seconds
+    pub fn rotate(
+        &self,
+        name: &[u8; TICKET_KEY_NAME_LEN],
+        key: &[u8; TICKET_KEY_LEN],
+        lifetime: u32,
+    ) -> Result<(), MbedtlsError> {
+        merr!(unsafe {
+            mbedtls_ssl_ticket_rotate(
+                &*self.ctx as *const _ as *mut _,
+                name.as_ptr(),
+                name.len(),
+                key.as_ptr(),
+                key.len(),
+                lifetime,
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Get the lifetime of the issued tickets, in seconds
+    pub fn lifetime(&self) -> u32 {
+        self.ctx.private_ticket_lifetime
+    }
+
+    /// Get a raw pointer to the ticket context, to be used as the MbedTLS ticket callbacks' context
+    pub(crate) fn as_ptr(&self) -> *mut c_void {
+        &*self.ctx as *const _ as *mut c_void
+    }
+}
+
+impl core::fmt::Debug for SessionTickets<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SessionTickets")
+            .field("lifetime", &self.lifetime())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SessionTickets<'_> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "SessionTickets {{ lifetime: {}, .. }}", self.lifetime())
+    }
+}
+
+impl MInit for mbedtls_ssl_ticket_context {
+    fn init(&mut self) {
+        unsafe {
+            mbedtls_ssl_ticket_init(self);
+        }
+    }
+
+    fn deinit(&mut self) {
+        unsafe {
+            mbedtls_ssl_ticket_free(self);
+        }
+    }
+}
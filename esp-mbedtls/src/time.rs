@@ -0,0 +1,206 @@
+use core::cell::RefCell;
+use core::time::Duration;
+
+use critical_section::Mutex;
+
+/// A wall-clock time source
+///
+/// Used by MbedTLS for checking the validity period of the peer certificates and of the CRLs,
+/// as well as for expiring session tickets and cached sessions.
+///
+/// Registered with `Tls::set_time_provider`. Without a time provider (or while the provider
+/// does not know the current time), the validity periods are not checked.
+///
+/// MbedTLS also measures the age of TLS 1.3 session tickets with it. As the time is then frozen
+/// at the Unix epoch, a TLS 1.3 server without a time provider sees its tickets as just issued,
+/// and thus does not resume the sessions of clients reporting tickets older than
+/// `MBEDTLS_SSL_TLS1_3_TICKET_AGE_TOLERANCE` (6 seconds by default), falling back to a full handshake.
+pub trait TimeProvider {
+    /// Get the current time
+    ///
+    /// # Returns
+    /// - The time elapsed since the Unix epoch (UTC), or `None` if the current time is not known
+    ///   (e.g. not synchronized over SNTP yet)
+    fn now(&self) -> Option<Duration>;
+}
+
+impl core::fmt::Debug for dyn TimeProvider + '_ {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "TimeProvider")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for dyn TimeProvider + '_ {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "TimeProvider")
+    }
+}
+
+/// A time provider using the system time of the host
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdTime;
+
+#[cfg(feature = "std")]
+impl TimeProvider for StdTime {
+    fn now(&self) -> Option<Duration> {
+        std::time::SystemTime::now()
+            .duration_since(std::time::SystemTime::UNIX_EPOCH)
+            .ok()
+    }
+}
+
+pub(crate) static TIME: Mutex<RefCell<Option<&(dyn TimeProvider + Sync)>>> =
+    Mutex::new(RefCell::new(None));
+
+/// Get the current time from the registered time provider, if any
+pub(crate) fn now() -> Option<Duration> {
+    critical_section::with(|cs| *TIME.borrow(cs).borrow()).and_then(|time| time.now())
+}
+
+#[cfg(all(not(target_os = "espidf"), mbedtls_platform_time_alt))]
+pub(crate) use hook::*;
+
+/// The MbedTLS platform time functions (`MBEDTLS_PLATFORM_TIME_ALT`, `MBEDTLS_PLATFORM_MS_TIME_ALT`
+/// and `MBEDTLS_PLATFORM_GMTIME_R_ALT`), implemented on top of the registered time provider
+///
+/// Only available if MbedTLS is built with these options, which `gen/include/config.h` enables together.
+#[cfg(all(not(target_os = "espidf"), mbedtls_platform_time_alt))]
+mod hook {
+    use crate::sys::{mbedtls_ms_time_t, mbedtls_platform_set_time, mbedtls_time_t, tm};
+
+    /// Hook the time provider into MbedTLS
+    pub(crate) fn hook_time() {
+        unsafe {
+            mbedtls_platform_set_time(Some(mbedtls_time));
+        }
+    }
+
+    /// The current time in seconds since the Unix epoch, or 0 if unknown
+    unsafe extern "C" fn mbedtls_time(timer: *mut mbedtls_time_t) -> mbedtls_time_t {
+        let now = super::now().map(|now| now.as_secs() as _).unwrap_or(0);
+
+        if let Some(timer) = timer.as_mut() {
+            *timer = now;
+        }
+
+        now
+    }
+
+    /// The current time in milliseconds, or 0 if unknown
+    ///
+    /// Only time differences are relevant to MbedTLS, so the Unix epoch is used as the reference.
+    #[no_mangle]
+    unsafe extern "C" fn mbedtls_ms_time() -> mbedtls_ms_time_t {
+        super::now().map(|now| now.as_millis() as _).unwrap_or(0)
+    }
+
+    /// Convert a Unix timestamp into a UTC calendar time
+    #[no_mangle]
+    unsafe extern "C" fn mbedtls_platform_gmtime_r(
+        tt: *const mbedtls_time_t,
+        tm_buf: *mut tm,
+    ) -> *mut tm {
+        let (Some(tt), Some(tm)) = (tt.as_ref(), tm_buf.as_mut()) else {
+            return core::ptr::null_mut();
+        };
+
+        gmtime(*tt as _, tm);
+
+        tm
+    }
+
+    /// Convert a Unix timestamp (in seconds) into a UTC calendar time
+    fn gmtime(secs: i64, tm: &mut tm) {
+        let days = secs.div_euclid(86400);
+        let secs_of_day = secs.rem_euclid(86400);
+
+        // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + (month <= 2) as i64;
+
+        let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        // Day of the year, counted from January 1st
+        let yday = if mp < 10 {
+            doy + 59 + leap as i64
+        } else {
+            doy - 306
+        };
+
+        // Assigned field by field, as `struct tm` might have additional, libc-specific fields
+        tm.tm_sec = (secs_of_day % 60) as _;
+        tm.tm_min = (secs_of_day / 60 % 60) as _;
+        tm.tm_hour = (secs_of_day / 3600) as _;
+        tm.tm_mday = day as _;
+        tm.tm_mon = (month - 1) as _;
+        tm.tm_year = (year - 1900) as _;
+        // January 1st 1970 was a Thursday
+        tm.tm_wday = (days + 4).rem_euclid(7) as _;
+        tm.tm_yday = yday as _;
+        tm.tm_isdst = 0;
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::sys::tm;
+
+        /// Convert `secs` and return the calendar time as
+        /// `(year, month, day, hour, minute, second, day of week, day of year)`,
+        /// with the month and the day of the month counted from 1
+        fn civil(secs: i64) -> (i32, i32, i32, i32, i32, i32, i32, i32) {
+            let mut tm: tm = unsafe { core::mem::zeroed() };
+
+            super::gmtime(secs, &mut tm);
+
+            assert_eq!(tm.tm_isdst, 0);
+
+            (
+                tm.tm_year + 1900,
+                tm.tm_mon + 1,
+                tm.tm_mday,
+                tm.tm_hour,
+                tm.tm_min,
+                tm.tm_sec,
+                tm.tm_wday,
+                tm.tm_yday,
+            )
+        }
+
+        #[test]
+        fn epoch() {
+            assert_eq!(civil(0), (1970, 1, 1, 0, 0, 0, 4, 0));
+            assert_eq!(civil(-1), (1969, 12, 31, 23, 59, 59, 3, 364));
+            assert_eq!(civil(86399), (1970, 1, 1, 23, 59, 59, 4, 0));
+        }
+
+        #[test]
+        fn leap_days() {
+            // Divisible by 400, thus a leap year
+            assert_eq!(civil(951_782_400), (2000, 2, 29, 0, 0, 0, 2, 59));
+            assert_eq!(civil(951_868_800), (2000, 3, 1, 0, 0, 0, 3, 60));
+            assert_eq!(civil(1_709_251_199), (2024, 2, 29, 23, 59, 59, 4, 59));
+            assert_eq!(civil(1_735_689_599), (2024, 12, 31, 23, 59, 59, 2, 365));
+            // Not a leap year
+            assert_eq!(civil(1_740_787_200), (2025, 3, 1, 0, 0, 0, 6, 59));
+            // Divisible by 100 but not by 400, thus not a leap year
+            assert_eq!(civil(4_107_456_000), (2100, 2, 28, 0, 0, 0, 0, 58));
+            assert_eq!(civil(4_107_542_400), (2100, 3, 1, 0, 0, 0, 1, 59));
+        }
+
+        #[test]
+        fn beyond_2038() {
+            assert_eq!(civil(2_147_483_647), (2038, 1, 19, 3, 14, 7, 2, 18));
+            assert_eq!(civil(2_147_483_648), (2038, 1, 19, 3, 14, 8, 2, 18));
+            assert_eq!(civil(4_102_444_800), (2100, 1, 1, 0, 0, 0, 5, 0));
+            assert_eq!(civil(253_402_300_799), (9999, 12, 31, 23, 59, 59, 5, 364));
+        }
+    }
+}
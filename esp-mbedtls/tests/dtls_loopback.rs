@@ -0,0 +1,232 @@
+//! DTLS client and server sessions talking to each other over loopback UDP
+//!
+//! The client sends a message which the server echoes back. The first datagram of the client
+//! is dropped, so that the handshake only completes once the client retransmits its ClientHello,
+//! as driven by the retransmission timer. A small MTU is used, so that the handshake messages
+//! carrying the certificates are fragmented, and the server verifies the address of the client
+//! with a HelloVerifyRequest cookie exchange.
+
+#![cfg(mbedtls_ssl_dtls_hello_verify)]
+
+use core::ffi::CStr;
+use core::net::{Ipv4Addr, SocketAddr};
+use core::time::Duration;
+
+use std::io;
+use std::net::UdpSocket;
+use std::time::Instant;
+
+use esp_mbedtls::blocking::io::ErrorType;
+use esp_mbedtls::blocking::{Datagram, DtlsSession, DtlsTimer};
+use esp_mbedtls::{
+    Certificate, ClientSessionConfig, Credentials, DtlsCookies, PrivateKey, ServerSessionConfig,
+    SessionConfig, SessionError, Tls, TlsReference, X509,
+};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CERT: &[u8] = include_bytes!("../../examples/common/certs/cert.der");
+const KEY: &[u8] = include_bytes!("../../examples/common/certs/key.der");
+
+/// The name the certificate is issued for
+const SERVER_NAME: &CStr = c"esp-mbedtls.local";
+
+/// The maximum size of the datagrams sent by both peers
+const MTU: u16 = 512;
+
+/// The maximum time to wait for a datagram, so that a failing peer does not hang the test
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MESSAGE: &[u8] = b"Hello over DTLS!";
+
+#[test]
+fn dtls_loopback() {
+    let mut rng = StdRng::from_os_rng();
+    let tls = Tls::new(&mut rng).unwrap();
+
+    let server_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server_addr = server_socket.local_addr().unwrap();
+
+    let client_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    client_socket.connect(server_addr).unwrap();
+
+    let mut client_datagram = UdpDatagram::new(client_socket);
+    client_datagram.drop_next = true;
+
+    std::thread::scope(|s| {
+        let tls = tls.reference();
+
+        let server = s.spawn(move || {
+            // Wait for the first datagram of the client, so as to connect the socket to it
+            server_socket
+                .set_read_timeout(Some(RECEIVE_TIMEOUT))
+                .unwrap();
+            let (_, client_addr) = server_socket.peek_from(&mut [0; 1]).unwrap();
+            server_socket.connect(client_addr).unwrap();
+
+            server(tls, UdpDatagram::new(server_socket), client_addr)
+        });
+
+        client(tls, &mut client_datagram).unwrap();
+        server.join().unwrap().unwrap();
+    });
+
+    assert!(
+        client_datagram.retransmitted,
+        "The dropped ClientHello was not retransmitted"
+    );
+}
+
+fn server(
+    tls: TlsReference<'_>,
+    datagram: UdpDatagram,
+    client_addr: SocketAddr,
+) -> Result<(), SessionError> {
+    let mut session = DtlsSession::new(
+        tls,
+        datagram,
+        Clock::new(),
+        MTU,
+        &SessionConfig::Server(ServerSessionConfig {
+            dtls_cookies: Some(DtlsCookies::new(tls)?),
+            ..ServerSessionConfig::new(Credentials {
+                certificate: Certificate::new_no_copy(CERT)?,
+                private_key: PrivateKey::new(X509::DER(KEY), None)?,
+            })
+        }),
+    )?;
+
+    // The cookies are bound to the address of the client
+    session.set_client_addr(client_addr);
+
+    session.connect()?;
+
+    let mut buf = [0; 256];
+
+    let len = session.read(&mut buf)?;
+    session.write(&buf[..len])?;
+
+    // Wait for the close notify of the client
+    assert_eq!(session.read(&mut buf)?, 0);
+
+    Ok(())
+}
+
+fn client(tls: TlsReference<'_>, datagram: &mut UdpDatagram) -> Result<(), SessionError> {
+    let mut session = DtlsSession::new(
+        tls,
+        datagram,
+        Clock::new(),
+        MTU,
+        &SessionConfig::Client(ClientSessionConfig {
+            ca_chain: Some(Certificate::new_no_copy(CERT)?),
+            server_name: Some(SERVER_NAME),
+            ..ClientSessionConfig::new()
+        }),
+    )?;
+
+    session.connect()?;
+
+    session.write(MESSAGE)?;
+
+    let mut buf = [0; 256];
+    let len = session.read(&mut buf)?;
+
+    assert_eq!(&buf[..len], MESSAGE);
+
+    session.close()
+}
+
+/// A `Datagram` transport over a connected UDP socket, which can drop the next datagram to send
+struct UdpDatagram {
+    socket: UdpSocket,
+    /// Whether to drop the next datagram to send
+    drop_next: bool,
+    /// Whether a datagram was dropped, and nothing was received since
+    dropped: bool,
+    /// Whether a datagram was sent after the dropped one without anything received in between,
+    /// i.e. whether the dropped datagram was retransmitted on timeout
+    retransmitted: bool,
+}
+
+impl UdpDatagram {
+    fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            drop_next: false,
+            dropped: false,
+            retransmitted: false,
+        }
+    }
+}
+
+impl ErrorType for UdpDatagram {
+    type Error = io::Error;
+}
+
+impl Datagram for UdpDatagram {
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        assert!(data.len() <= MTU as usize, "Datagram larger than the MTU");
+
+        if self.drop_next {
+            self.drop_next = false;
+            self.dropped = true;
+
+            return Ok(());
+        }
+
+        if self.dropped {
+            self.retransmitted = true;
+        }
+
+        self.socket.send(data)?;
+
+        Ok(())
+    }
+
+    fn receive(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<usize>, Self::Error> {
+        self.socket
+            .set_read_timeout(Some(timeout.unwrap_or(RECEIVE_TIMEOUT)))?;
+
+        match self.socket.recv(buf) {
+            Ok(len) => {
+                self.dropped = false;
+
+                Ok(Some(len))
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                if timeout.is_some() {
+                    Ok(None)
+                } else {
+                    Err(e)
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A monotonic clock using the `std` monotonic clock
+struct Clock(Instant);
+
+impl Clock {
+    fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl DtlsTimer for Clock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
@@ -0,0 +1,241 @@
+//! Async DTLS client session and `DtlsAcceptor` talking to each other over loopback UDP
+//!
+//! The client sends a message which the server echoes back, and the server then closes the session.
+//! The first datagram of the client is dropped, so that the handshake only completes once the client
+//! retransmits its ClientHello, as driven by the retransmission timer. A small MTU is used, so that
+//! the handshake messages carrying the certificates are fragmented, and connection IDs are negotiated.
+
+#![cfg(all(feature = "edge-nal", mbedtls_ssl_dtls_connection_id))]
+
+use core::ffi::CStr;
+use core::future::poll_fn;
+use core::net::{Ipv4Addr, SocketAddr};
+use core::task::Poll;
+use core::time::Duration;
+
+use std::io;
+use std::net::UdpSocket;
+use std::time::Instant;
+
+use esp_mbedtls::io::ErrorType;
+use esp_mbedtls::nal::{UdpReceive, UdpSend};
+use esp_mbedtls::{
+    Certificate, ClientSessionConfig, Credentials, DtlsAcceptor, DtlsSession, DtlsTimer,
+    PrivateKey, ServerSessionConfig, SessionConfig, SessionError, Tls, X509,
+};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CERT: &[u8] = include_bytes!("../../examples/common/certs/cert.der");
+const KEY: &[u8] = include_bytes!("../../examples/common/certs/key.der");
+
+/// The name the certificate is issued for
+const SERVER_NAME: &CStr = c"esp-mbedtls.local";
+
+/// The maximum size of the datagrams sent by both peers
+const MTU: u16 = 512;
+
+/// The maximum time to wait for a datagram, so that a failing peer does not hang the test
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MESSAGE: &[u8] = b"Hello over async DTLS!";
+
+#[test]
+fn dtls_loopback_async() {
+    let mut rng = StdRng::from_os_rng();
+    let tls = Tls::new(&mut rng).unwrap();
+
+    let server_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server_addr = server_socket.local_addr().unwrap();
+
+    let client_socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+
+    let clock = Clock::new();
+
+    let mut rx_buf = [0; MTU as usize];
+
+    let mut server = DtlsAcceptor::<_, _, 2>::new(
+        tls.reference(),
+        UdpIo::new(server_socket),
+        &clock,
+        &mut rx_buf,
+        MTU,
+        &ServerSessionConfig {
+            connection_id_len: Some(4),
+            ..ServerSessionConfig::new(Credentials {
+                certificate: Certificate::new_no_copy(CERT).unwrap(),
+                private_key: PrivateKey::new(X509::DER(KEY), None).unwrap(),
+            })
+        },
+    )
+    .unwrap();
+
+    let mut client_io = UdpIo::new(client_socket);
+    client_io.drop_next = true;
+
+    let mut client = DtlsSession::new(
+        tls.reference(),
+        client_io,
+        Some(server_addr),
+        &clock,
+        MTU,
+        &SessionConfig::Client(ClientSessionConfig {
+            ca_chain: Some(Certificate::new_no_copy(CERT).unwrap()),
+            server_name: Some(SERVER_NAME),
+            connection_id_len: Some(0),
+            ..ClientSessionConfig::new()
+        }),
+    )
+    .unwrap();
+
+    let (server_result, client_result) = futures_lite::future::block_on(futures_lite::future::zip(
+        async {
+            let mut buf = [0; 256];
+
+            let (len, client_addr) = server.receive(&mut buf).await?;
+
+            assert!(server.connection_info(client_addr).is_some());
+
+            server.send(client_addr, &buf[..len]).await?;
+
+            server.close(client_addr).await?;
+
+            assert_eq!(server.peers().count(), 0);
+
+            Ok::<_, SessionError>(())
+        },
+        async {
+            client.connect().await?;
+
+            client.write(MESSAGE).await?;
+
+            let mut buf = [0; 256];
+            let len = client.read(&mut buf).await?;
+
+            assert_eq!(&buf[..len], MESSAGE);
+
+            // Wait for the close notify of the server
+            assert_eq!(client.read(&mut buf).await?, 0);
+
+            client.close().await
+        },
+    ));
+
+    server_result.unwrap();
+    client_result.unwrap();
+
+    assert!(
+        client.socket().retransmitted,
+        "The dropped ClientHello was not retransmitted"
+    );
+}
+
+/// An async datagram socket over a non-blocking UDP socket, which can drop the next datagram to send
+///
+/// Waiting for a datagram busy-polls the socket, which is good enough for a test.
+struct UdpIo {
+    socket: UdpSocket,
+    /// Whether to drop the next datagram to send
+    drop_next: bool,
+    /// Whether a datagram was dropped, and nothing was received since
+    dropped: bool,
+    /// Whether a datagram was sent after the dropped one without anything received in between,
+    /// i.e. whether the dropped datagram was retransmitted on timeout
+    retransmitted: bool,
+}
+
+impl UdpIo {
+    fn new(socket: UdpSocket) -> Self {
+        socket.set_nonblocking(true).unwrap();
+
+        Self {
+            socket,
+            drop_next: false,
+            dropped: false,
+            retransmitted: false,
+        }
+    }
+}
+
+impl ErrorType for UdpIo {
+    type Error = io::Error;
+}
+
+impl UdpSend for UdpIo {
+    async fn send(&mut self, remote: SocketAddr, data: &[u8]) -> Result<(), Self::Error> {
+        assert!(data.len() <= MTU as usize, "Datagram larger than the MTU");
+
+        if self.drop_next {
+            self.drop_next = false;
+            self.dropped = true;
+
+            return Ok(());
+        }
+
+        if self.dropped {
+            self.retransmitted = true;
+        }
+
+        self.socket.send_to(data, remote)?;
+
+        Ok(())
+    }
+}
+
+impl UdpReceive for UdpIo {
+    async fn receive(&mut self, buf: &mut [u8]) -> Result<(usize, SocketAddr), Self::Error> {
+        let start = Instant::now();
+
+        let received = poll_fn(|ctx| match self.socket.recv_from(buf) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                if start.elapsed() >= RECEIVE_TIMEOUT {
+                    return Poll::Ready(Err(io::ErrorKind::TimedOut.into()));
+                }
+
+                ctx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            result => Poll::Ready(result),
+        })
+        .await?;
+
+        self.dropped = false;
+
+        Ok(received)
+    }
+}
+
+/// A monotonic clock using the `std` monotonic clock
+///
+/// Like a hardware timer, a wait is only armed by the first poll of its future, so it never completes
+/// on that poll, even when the deadline is already reached.
+struct Clock(Instant);
+
+impl Clock {
+    fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl DtlsTimer for Clock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+
+    async fn wait_until(&self, deadline: Duration) {
+        let mut armed = false;
+
+        poll_fn(|ctx| {
+            if armed && self.now() >= deadline {
+                return Poll::Ready(());
+            }
+
+            armed = true;
+
+            ctx.waker().wake_by_ref();
+            Poll::Pending
+        })
+        .await
+    }
+}
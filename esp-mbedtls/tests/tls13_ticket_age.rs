@@ -0,0 +1,214 @@
+//! TLS 1.3 session resumption with a server which has no time provider
+//!
+//! Without a time provider, MbedTLS sees the time of the server frozen at the Unix epoch, so the
+//! server cannot measure the age of its session tickets. A ticket is then only accepted as long as
+//! the age reported by the client is within `MBEDTLS_SSL_TLS1_3_TICKET_AGE_TOLERANCE`, beyond which
+//! the server falls back to a full handshake.
+//!
+//! The time provider is shared by both peers, so it only knows the time on the client thread,
+//! where the clock can be moved forward instead of waiting for the tolerance to elapse.
+
+#![cfg(mbedtls_platform_time_alt)]
+
+use core::cell::Cell;
+use core::ffi::CStr;
+use core::net::{Ipv4Addr, SocketAddr};
+use core::time::Duration;
+
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use esp_mbedtls::blocking::io::{ErrorType, Read, Write};
+use esp_mbedtls::blocking::Session;
+use esp_mbedtls::{
+    Certificate, ClientSessionConfig, Credentials, PrivateKey, ServerSessionConfig, SessionConfig,
+    SessionError, SessionTickets, TimeProvider, Tls, TlsReference, TlsVersion, X509,
+};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CERT: &[u8] = include_bytes!("../../examples/common/certs/cert.der");
+const KEY: &[u8] = include_bytes!("../../examples/common/certs/key.der");
+
+/// The name the certificate is issued for
+const SERVER_NAME: &CStr = c"esp-mbedtls.local";
+
+/// The wall-clock time of the client when the test starts, within the validity period of the certificate
+const CLIENT_EPOCH: Duration = Duration::from_secs(1_748_736_000);
+
+/// How far the clock of the client is moved forward, beyond the ticket age tolerance of the server
+const CLIENT_CLOCK_SKIP: Duration = Duration::from_secs(10);
+
+/// The lifetime of the session tickets, in seconds
+const TICKET_LIFETIME: u32 = 3600;
+
+const MESSAGE: &[u8] = b"Hello over TLS 1.3!";
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+thread_local! {
+    /// How far the clock is moved forward on the current thread, or `None` if the time is not known
+    static CLOCK_SKIP: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// A time provider which only knows the time on the client thread
+struct ClientTime;
+
+impl TimeProvider for ClientTime {
+    fn now(&self) -> Option<Duration> {
+        CLOCK_SKIP
+            .get()
+            .map(|skip| CLIENT_EPOCH + START.elapsed() + skip)
+    }
+}
+
+#[test]
+fn tls13_ticket_age() {
+    let mut rng = StdRng::from_os_rng();
+    let mut tls = Tls::new(&mut rng).unwrap();
+
+    tls.set_time_provider(Some(&ClientTime));
+
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let server_addr = listener.local_addr().unwrap();
+
+    std::thread::scope(|s| {
+        let tls = tls.reference();
+
+        let server = s.spawn(move || -> Result<(), SessionError> {
+            let session_tickets = SessionTickets::new(tls, TICKET_LIFETIME)?;
+
+            for _ in 0..3 {
+                let (stream, _) = listener.accept().unwrap();
+
+                server(tls, TcpIo(stream), &session_tickets)?;
+            }
+
+            Ok(())
+        });
+
+        CLOCK_SKIP.set(Some(Duration::ZERO));
+
+        let mut saved = [0; 2048];
+
+        // A full handshake, with the server issuing a ticket
+        let (resumed, len) = client(tls, server_addr, None, &mut saved).unwrap();
+        assert!(!resumed);
+
+        // The ticket is used right away, so its age is within the tolerance
+        let (resumed, _) = client(tls, server_addr, Some(&saved[..len]), &mut [0; 2048]).unwrap();
+        assert!(
+            resumed,
+            "The ticket was not accepted within the age tolerance"
+        );
+
+        // The ticket is used after the tolerance, as seen by the client
+        CLOCK_SKIP.set(Some(CLIENT_CLOCK_SKIP));
+
+        let (resumed, _) = client(tls, server_addr, Some(&saved[..len]), &mut [0; 2048]).unwrap();
+        assert!(!resumed, "The ticket was accepted beyond the age tolerance");
+
+        server.join().unwrap().unwrap();
+    });
+}
+
+fn server(
+    tls: TlsReference<'_>,
+    stream: TcpIo,
+    session_tickets: &SessionTickets<'_>,
+) -> Result<(), SessionError> {
+    let mut session = Session::new(
+        tls,
+        stream,
+        &SessionConfig::Server(ServerSessionConfig {
+            session_tickets: Some(session_tickets.clone()),
+            min_version: TlsVersion::Tls1_3,
+            ..ServerSessionConfig::new(Credentials {
+                certificate: Certificate::new_no_copy(CERT)?,
+                private_key: PrivateKey::new(X509::DER(KEY), None)?,
+            })
+        }),
+    )?;
+
+    session.connect()?;
+
+    let mut buf = [0; 256];
+
+    let len = session.read(&mut buf)?;
+    session.write(&buf[..len])?;
+
+    // Wait for the close notify of the client
+    assert_eq!(session.read(&mut buf)?, 0);
+
+    Ok(())
+}
+
+/// Connect to the server, resuming the `resume` session if provided, and save the session into `saved`
+///
+/// # Returns
+/// - Whether the session was resumed, and the length of the saved session
+fn client(
+    tls: TlsReference<'_>,
+    server_addr: SocketAddr,
+    resume: Option<&[u8]>,
+    saved: &mut [u8],
+) -> Result<(bool, usize), SessionError> {
+    let mut session = Session::new(
+        tls,
+        TcpIo(TcpStream::connect(server_addr).unwrap()),
+        &SessionConfig::Client(ClientSessionConfig {
+            ca_chain: Some(Certificate::new_no_copy(CERT)?),
+            server_name: Some(SERVER_NAME),
+            min_version: TlsVersion::Tls1_3,
+            ..ClientSessionConfig::new()
+        }),
+    )?;
+
+    if let Some(resume) = resume {
+        session.load_session(resume)?;
+    }
+
+    session.connect()?;
+
+    let resumed = session.connection_info().resumed;
+
+    session.write(MESSAGE)?;
+
+    // The ticket of the server is received before the echoed message
+    let mut buf = [0; 256];
+    let len = session.read(&mut buf)?;
+
+    assert_eq!(&buf[..len], MESSAGE);
+
+    let len = session.save_session(saved)?;
+
+    session.close()?;
+
+    Ok((resumed, len))
+}
+
+/// A blocking `embedded-io` stream over a TCP stream
+struct TcpIo(TcpStream);
+
+impl ErrorType for TcpIo {
+    type Error = io::Error;
+}
+
+impl Read for TcpIo {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        io::Read::read(&mut self.0, buf)
+    }
+}
+
+impl Write for TcpIo {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        io::Write::write(&mut self.0, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        io::Write::flush(&mut self.0)
+    }
+}
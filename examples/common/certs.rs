@@ -49,3 +49,13 @@ pub fn server_conf(mtls: bool) -> ServerSessionConfig<'static> {
 
     conf
 }
+
+/// A client configuration trusting the self-signed certificate of `server_conf`,
+/// for connecting to a server running on the same host
+pub fn loopback_client_conf() -> ClientSessionConfig<'static> {
+    ClientSessionConfig {
+        ca_chain: Some(Certificate::new_no_copy(CERT).unwrap()),
+        server_name: Some(c"esp-mbedtls.local"),
+        ..ClientSessionConfig::new()
+    }
+}
@@ -7,7 +7,7 @@ use std::net::{TcpStream, ToSocketAddrs};
 
 use embedded_io_adapters::std::FromStd;
 
-use esp_mbedtls::Tls;
+use esp_mbedtls::{StdTime, Tls};
 
 use log::info;
 
@@ -26,6 +26,8 @@ fn main() {
     let mut rng = rng::StdRng;
     let mut tls = Tls::new(&mut rng).unwrap();
 
+    tls.set_time_provider(Some(&StdTime));
+
     tls.set_debug(1);
 
     for (index, (server_name_cstr, server_path, mtls)) in [
@@ -9,7 +9,7 @@ use async_io_mini::Async;
 
 use embedded_io_adapters::futures_03::FromFutures;
 
-use esp_mbedtls::Tls;
+use esp_mbedtls::{StdTime, Tls};
 
 use log::info;
 
@@ -34,6 +34,8 @@ async fn run(buf: &mut [u8]) {
     let mut rng = rng::StdRng;
     let mut tls = Tls::new(&mut rng).unwrap();
 
+    tls.set_time_provider(Some(&StdTime));
+
     tls.set_debug(1);
 
     for (index, (server_name_cstr, server_path, mtls)) in [
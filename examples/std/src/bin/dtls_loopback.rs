@@ -0,0 +1,301 @@
+//! Example of DTLS client and server sessions talking to each other over loopback UDP,
+//! first using the blocking API, then using the async API over `edge-nal`, with a `DtlsAcceptor`
+//! as the server.
+//!
+//! The client sends a message which the server echoes back. A small MTU is used,
+//! so that the handshake messages carrying the certificates are fragmented.
+//! The server verifies the address of the client with a HelloVerifyRequest cookie exchange,
+//! and in the async case, connection IDs are negotiated as well.
+//!
+//! The example panics if anything goes wrong, so it doubles as a host test:
+//! ```sh
+//! cargo run --bin dtls_loopback
+//! ```
+
+use core::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use core::time::Duration;
+
+use std::net::UdpSocket;
+use std::time::Instant;
+
+use embassy_time::Timer;
+
+use esp_mbedtls::blocking::io::ErrorType;
+use esp_mbedtls::blocking::{self, Datagram};
+use esp_mbedtls::nal::{UdpBind, UdpReceive, UdpSend};
+use esp_mbedtls::{
+    ClientSessionConfig, DtlsAcceptor, DtlsCookies, DtlsSession, DtlsTimer, ServerSessionConfig,
+    SessionConfig, SessionError, Tls, TlsReference,
+};
+
+use log::info;
+
+#[path = "../bootstrap.rs"]
+mod bootstrap;
+#[path = "../../../common/certs.rs"]
+mod certs;
+#[path = "../../../common/std_rng.rs"]
+mod rng;
+
+/// The maximum size of the datagrams sent by both peers
+const MTU: u16 = 512;
+
+const MESSAGE: &[u8] = b"Hello over DTLS!";
+
+fn main() {
+    bootstrap::bootstrap();
+
+    info!("Initializing TLS");
+
+    let mut rng = rng::StdRng;
+    let mut tls = Tls::new(&mut rng).unwrap();
+
+    tls.set_debug(1);
+
+    run_blocking(&tls);
+
+    bootstrap::block_on(run_async(&tls));
+
+    info!("Done");
+}
+
+/// Run the echo exchange with the blocking API, with the server and the client in separate threads
+fn run_blocking(tls: &Tls<'_>) {
+    info!("\n\n\n\nBLOCKING =============================");
+
+    let server_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 5684));
+
+    let server_socket = UdpSocket::bind(server_addr).unwrap();
+    let client_socket =
+        UdpSocket::bind(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0))).unwrap();
+
+    std::thread::scope(|s| {
+        let tls = tls.reference();
+
+        s.spawn(move || {
+            // Wait for the first datagram of the client, so as to connect the socket to it
+            let (_, client_addr) = server_socket.peek_from(&mut [0; 1]).unwrap();
+            server_socket.connect(client_addr).unwrap();
+
+            blocking_server(tls, StdDatagram(server_socket), client_addr).unwrap();
+        });
+
+        client_socket.connect(server_addr).unwrap();
+
+        blocking_client(tls, StdDatagram(client_socket)).unwrap();
+    });
+}
+
+fn blocking_server(
+    tls: TlsReference<'_>,
+    datagram: StdDatagram,
+    client_addr: SocketAddr,
+) -> Result<(), SessionError> {
+    let mut session = blocking::DtlsSession::new(
+        tls,
+        datagram,
+        StdClock::new(),
+        MTU,
+        &SessionConfig::Server(ServerSessionConfig {
+            dtls_cookies: Some(DtlsCookies::new(tls)?),
+            ..certs::server_conf(false)
+        }),
+    )?;
+
+    // The cookies are bound to the address of the client
+    session.set_client_addr(client_addr);
+
+    session.connect()?;
+
+    info!("Server: connected, {:?}", session.connection_info());
+
+    let mut buf = [0; 256];
+
+    let len = session.read(&mut buf)?;
+    session.write(&buf[..len])?;
+
+    // Wait for the close notify of the client
+    assert_eq!(session.read(&mut buf)?, 0);
+
+    Ok(())
+}
+
+fn blocking_client(tls: TlsReference<'_>, datagram: StdDatagram) -> Result<(), SessionError> {
+    let mut session = blocking::DtlsSession::new(
+        tls,
+        datagram,
+        StdClock::new(),
+        MTU,
+        &SessionConfig::Client(certs::loopback_client_conf()),
+    )?;
+
+    session.connect()?;
+
+    info!("Client: connected, {:?}", session.connection_info());
+
+    session.write(MESSAGE)?;
+
+    let mut buf = [0; 256];
+    let len = session.read(&mut buf)?;
+
+    assert_eq!(&buf[..len], MESSAGE);
+
+    info!("Client: received the echo");
+
+    session.close()
+}
+
+/// Run the echo exchange with the async API, with the server and the client in the same task
+async fn run_async(tls: &Tls<'_>) {
+    info!("\n\n\n\nASYNC =============================");
+
+    let stack = edge_nal_std::Stack::new();
+
+    let server_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 5684));
+
+    let server_socket = stack.bind(server_addr).await.unwrap();
+    let client_socket = stack
+        .bind(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0)))
+        .await
+        .unwrap();
+
+    let mut rx_buf = [0; MTU as usize];
+
+    // Serves up to 4 clients at a time, with cookies generated by the acceptor itself
+    let mut server = DtlsAcceptor::<_, _, 4>::new(
+        tls.reference(),
+        server_socket,
+        EmbassyTimer,
+        &mut rx_buf,
+        MTU,
+        &ServerSessionConfig {
+            // The client has to put this 4-byte ID of its session in the records it sends to the server
+            connection_id_len: Some(4),
+            ..certs::server_conf(false)
+        },
+    )
+    .unwrap();
+
+    let mut client = DtlsSession::new(
+        tls.reference(),
+        client_socket,
+        Some(server_addr),
+        EmbassyTimer,
+        MTU,
+        &SessionConfig::Client(ClientSessionConfig {
+            // No ID is needed for the records sent to the client, as the server address does not change
+            connection_id_len: Some(0),
+            ..certs::loopback_client_conf()
+        }),
+    )
+    .unwrap();
+
+    let (server_result, client_result) = futures_lite::future::zip(
+        async {
+            let mut buf = [0; 256];
+
+            let (len, client_addr) = server.receive(&mut buf).await?;
+
+            info!(
+                "Server: received from {}, {:?}",
+                client_addr,
+                server.connection_info(client_addr)
+            );
+
+            server.send(client_addr, &buf[..len]).await?;
+
+            server.close(client_addr).await?;
+
+            Ok::<_, SessionError>(())
+        },
+        async {
+            client.connect().await?;
+
+            info!("Client: connected, {:?}", client.connection_info());
+
+            client.write(MESSAGE).await?;
+
+            let mut buf = [0; 256];
+            let len = client.read(&mut buf).await?;
+
+            assert_eq!(&buf[..len], MESSAGE);
+
+            info!("Client: received the echo");
+
+            // Wait for the close notify of the server
+            assert_eq!(client.read(&mut buf).await?, 0);
+
+            client.close().await
+        },
+    )
+    .await;
+
+    server_result.unwrap();
+    client_result.unwrap();
+}
+
+/// A `Datagram` transport over a connected `std` UDP socket
+struct StdDatagram(UdpSocket);
+
+impl ErrorType for StdDatagram {
+    type Error = std::io::Error;
+}
+
+impl Datagram for StdDatagram {
+    fn send(&mut self, data: &[u8]) -> Result<(), Self::Error> {
+        assert!(data.len() <= MTU as usize, "Datagram larger than the MTU");
+
+        self.0.send(data)?;
+
+        Ok(())
+    }
+
+    fn receive(
+        &mut self,
+        buf: &mut [u8],
+        timeout: Option<Duration>,
+    ) -> Result<Option<usize>, Self::Error> {
+        self.0.set_read_timeout(timeout)?;
+
+        match self.0.recv(buf) {
+            Ok(len) => Ok(Some(len)),
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A monotonic clock for the blocking sessions, using the `std` monotonic clock
+struct StdClock(Instant);
+
+impl StdClock {
+    fn new() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl blocking::DtlsTimer for StdClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// A timer for the async sessions, using `embassy-time`
+struct EmbassyTimer;
+
+impl DtlsTimer for EmbassyTimer {
+    fn now(&self) -> Duration {
+        Duration::from_micros(embassy_time::Instant::now().as_micros())
+    }
+
+    async fn wait_until(&self, deadline: Duration) {
+        Timer::at(embassy_time::Instant::from_micros(deadline.as_micros() as _)).await
+    }
+}
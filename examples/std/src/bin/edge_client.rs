@@ -3,7 +3,7 @@
 //! This example connects to `https://httpbin.org/ip` and then to `https://certauth.cryptomix.com/json/` (mTLS)
 //! and performs a simple HTTPS 1.1 GET request to each.
 
-use esp_mbedtls::Tls;
+use esp_mbedtls::{StdTime, Tls};
 
 use log::info;
 
@@ -28,6 +28,8 @@ async fn run(buf: &mut [u8]) {
     let mut rng = rng::StdRng;
     let mut tls = Tls::new(&mut rng).unwrap();
 
+    tls.set_time_provider(Some(&StdTime));
+
     tls.set_debug(1);
 
     let stack = edge_nal_std::Stack::new();